@@ -0,0 +1,1107 @@
+//! 配置文件 (config.json / config.toml) 的结构、默认值、读写和路径解析
+
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use crate::*;
+
+pub(crate) const MAX_SESSION_MINUTES: u64 = 24 * 60; // 单次专注/休息时长上限
+
+// 配置目录不可用时置位: 本次运行的配置、统计、记录都只留在内存里，不再尝试读写文件
+pub(crate) static MEMORY_ONLY: AtomicBool = AtomicBool::new(false);
+
+// 最近一次保存失败的原因。保存也会在后台线程进行，界面每帧取出来显示在错误提示里
+pub(crate) static SAVE_ERROR: std::sync::Mutex<Option<String>> = std::sync::Mutex::new(None);
+
+pub(crate) fn report_save_error(message: String) {
+    println!("{}", message);
+    *SAVE_ERROR.lock().unwrap_or_else(|e| e.into_inner()) = Some(message);
+}
+
+pub(crate) fn take_save_error() -> Option<String> {
+    SAVE_ERROR.lock().unwrap_or_else(|e| e.into_inner()).take()
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub(crate) struct AppConfig {
+    pub(crate) config_version: u32,           // 配置格式版本，导入时据此迁移旧文件、拒绝更新版本的文件
+    pub(crate) work_minutes: u64,
+    pub(crate) rest_minutes: u64,
+    pub(crate) long_rest_minutes: u64,
+    pub(crate) cycles_before_long_rest: u64,  // 每完成几个番茄进行一次长休息，0 表示关闭
+    pub(crate) auto_hide_when_idle_secs: u64, // 暂停状态下无操作多久后自动隐藏到托盘，0 表示关闭
+    pub(crate) high_contrast: bool,           // 高对比度模式 (黑/白/黄)
+    pub(crate) accent_colors: AccentColors,   // 倒计时文字和托盘图标的状态颜色
+    pub(crate) webhook_url: Option<String>,   // 专注/休息完成时 POST 通知的地址，None 表示关闭
+    // 专注/休息结束时运行的外部命令 (不经过 shell)，参数后追加: 类型 时长秒数 任务
+    pub(crate) on_work_end_cmd: Option<String>,
+    pub(crate) on_rest_end_cmd: Option<String>,
+    pub(crate) schedule: WorkSchedule,        // 按星期设置的工作时间，时间外不提醒
+    pub(crate) presentation_mode: bool,       // 演示模式：休息改为角落小窗提示，不弹全屏
+    pub(crate) auto_presentation_mode: bool,  // 检测到麦克风/摄像头占用时自动按演示模式处理 (仅 Windows)
+    pub(crate) steal_focus_on_show: bool,     // 从托盘唤醒时是否强制抢占前台焦点
+    pub(crate) overlay_monitor: OverlayMonitor,
+    pub(crate) overlay_all_monitors: bool,    // 多显示器时其它显示器也盖上休息画面
+    pub(crate) start_working_on_launch: bool, // 启动后立即开始专注
+    pub(crate) start_hidden: bool,            // 启动时直接隐藏到托盘 (配合开机自启静默运行)
+    pub(crate) auto_start_confirmed: bool,    // 开启开机自启前的注册表说明选了"不再提示"
+    pub(crate) last_seen_version: String,     // 上次展示过更新内容的版本
+    pub(crate) lock_first_session: bool,      // 每天第一个番茄及其休息不可暂停/跳过
+    pub(crate) overlay_title: String,         // 休息蒙层标题，{task} 替换为当前任务
+    pub(crate) quiet_hours_enabled: bool,     // 夜间静音：时段内照常计时，但不弹蒙层/小窗、不出声
+    pub(crate) quiet_hours_start: u32,        // 当天的分钟数，end < start 表示跨过午夜
+    pub(crate) quiet_hours_end: u32,
+    pub(crate) track_apps: bool,              // 专注时统计前台程序用时 (仅 Windows，默认关闭，数据只存本机)
+    pub(crate) tray_left_click_action: TrayClickAction,
+    pub(crate) breathing_guide: bool,         // 长休息时在蒙层上显示呼吸引导动画
+    pub(crate) emoji_spawn_rate: f32,         // 休息时每帧生成一批表情的概率 (0~1)
+    pub(crate) emoji_speed_range: [f32; 2],   // 表情下落速度范围 (像素/秒)
+    pub(crate) emojis_above_ui: bool,         // 表情画在按钮文字上面；默认画在界面背景上，不挡住跳过按钮
+    pub(crate) rest_end_action: RestEndAction,
+    pub(crate) rest_window_mode: RestWindowMode, // 休息蒙层全屏显示，还是居中的普通窗口 (严格模式总是全屏)
+    pub(crate) rest_count_direction: RestCountDirection,
+    pub(crate) open_ended_rest: bool, // 休息不限时，正计时直到点"结束休息" (严格模式下不生效)
+    pub(crate) intensity: Intensity,          // 休息强度，把提示方式、能否跳过、抢焦点合成一个选项
+    pub(crate) skip_nag_threshold: u32,       // 连续跳过这么多次休息后，下一次休息不可跳过；0 表示关闭
+    pub(crate) skip_cooldown_secs: u64,       // 跳过休息后这么多秒内不能开始专注；0 表示关闭
+    pub(crate) skip_undo_secs: u64,           // 跳过休息后这么多秒内可以撤销，回到休息；0 表示关闭
+    pub(crate) snooze_minutes: u64,           // 休息开始时点"推迟"，再专注这么久才休息
+    pub(crate) max_snoozes: u32,              // 每轮休息最多推迟几次；0 表示不显示推迟按钮
+    pub(crate) paused_nudge_minutes: u64,     // 暂停或未开始超过这么多分钟时闪烁托盘提醒开始专注，之后每隔这么久再提醒；0 表示关闭
+    pub(crate) current_task: String,          // 上次的当前任务和任务队列，重启后恢复
+    pub(crate) task_queue: Vec<String>,
+    pub(crate) ask_session_note: bool,        // 专注完成时弹出一句话记录，保存到 notes.jsonl
+    pub(crate) celebrate_work_done: bool,     // 专注完成时在主界面放一小把表情并显示"做得好"
+    pub(crate) require_break_ack: bool,       // 休息结束后蒙层保持显示，点"开始下一轮专注"才继续
+    pub(crate) smooth_transitions: bool,      // 休息蒙层淡入淡出
+    pub(crate) hold_to_skip: bool,            // 蒙层上的跳过和推迟按钮要按住 HOLD_TO_SKIP 才生效，提前松开取消
+    pub(crate) presets: Vec<Preset>,
+    pub(crate) overlay_click_through: bool,   // (实验，仅 Windows) 休息蒙层鼠标穿透，只有跳过按钮附近可点
+    pub(crate) overlay_borderless: bool,      // 休息蒙层用无边框置顶的最大化窗口代替全屏，Alt+Tab 和任务栏仍可用
+    pub(crate) mini_overlay: bool,            // 专注时在独立的置顶小窗里显示倒计时
+    pub(crate) mini_overlay_pos: Option<[f32; 2]>, // 小窗上次被拖到的位置 (逻辑坐标)
+    pub(crate) sound_enabled: bool,           // 阶段切换时播放提示音 (静音时段内不播放)
+    pub(crate) reminders: Vec<ReminderSetting>, // 健康提醒轮换，全部关闭时为普通休息
+    pub(crate) suppress_during_fullscreen: bool, // (仅 Windows) 其它程序全屏时推迟休息，直到退出全屏
+    pub(crate) daily_work_cap_minutes: u64,   // 今日专注超过多少分钟后提醒注意休息，0 表示关闭
+    pub(crate) stats_autosave_secs: u64,      // 每隔多少秒在后台保存一次统计 (含进行中的这一段)，0 表示只在阶段结束时保存
+    pub(crate) water_goal_cups: u32,          // 每日饮水目标 (杯)，统计里显示进度；0 表示关闭
+    pub(crate) cap_forces_long_rest: bool,    // 超过上限后每次休息都按长休息处理
+    pub(crate) tooltip_show_stats: bool,      // 托盘提示末尾附加今日完成的番茄数
+    pub(crate) tooltip_template: String,      // 计时中托盘提示的格式，支持 {state} {time} {task} {today_count}
+    pub(crate) time_format_24h: bool,         // 预计结束时间用 24 小时制，关闭时显示 12 小时制 AM/PM
+    pub(crate) show_next_task: bool,          // 休息快结束时在蒙层上显示任务队列里的下一个任务
+    pub(crate) idle_repaint_pause: bool,      // 暂停/未开始且隐藏到托盘时停止定时重绘，由托盘事件唤醒
+    pub(crate) minimize_to_taskbar: bool,     // 关闭/隐藏时最小化到任务栏，而不是隐藏到托盘
+    pub(crate) allow_maximize: bool,          // 显示最大化按钮 (启动时生效)
+    pub(crate) allow_resize: bool,            // 允许拖动边框调整窗口大小 (启动时生效)
+    pub(crate) auto_minimize_on_work: bool,   // 开始专注后自动收起窗口 (按上一项决定最小化还是隐藏到托盘)
+    pub(crate) auto_minimize_delay_secs: u64, // 开始专注后等几秒再收起，留时间看一眼
+    pub(crate) break_sound: String,           // 开始休息的提示音: 内置音 "builtin:*" 或声音文件夹里的文件名
+    pub(crate) work_start_sound: String,      // 开始专注的提示音，空字符串表示不播放
+    pub(crate) rest_end_sound: String,        // 休息结束的提示音，空字符串表示不播放
+    pub(crate) sounds_dir: String,            // 自定义声音文件夹，留空使用配置目录下的 sounds
+    pub(crate) audio_device: Option<String>,  // 指定的音频输出设备名，None 表示系统默认
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            config_version: CONFIG_VERSION,
+            work_minutes: 25,
+            rest_minutes: 5,
+            long_rest_minutes: 15,
+            cycles_before_long_rest: 4,
+            auto_hide_when_idle_secs: 0,
+            high_contrast: false,
+            accent_colors: AccentColors::default(),
+            webhook_url: None,
+            on_work_end_cmd: None,
+            on_rest_end_cmd: None,
+            schedule: WorkSchedule::default(),
+            presentation_mode: false,
+            auto_presentation_mode: false,
+            steal_focus_on_show: true,
+            overlay_monitor: OverlayMonitor::Current,
+            overlay_all_monitors: true,
+            start_working_on_launch: false,
+            start_hidden: false,
+            auto_start_confirmed: false,
+            last_seen_version: String::new(),
+            lock_first_session: false,
+            overlay_title: DEFAULT_OVERLAY_TITLE.to_string(),
+            quiet_hours_enabled: false,
+            quiet_hours_start: 22 * 60,
+            quiet_hours_end: 7 * 60,
+            track_apps: false,
+            tray_left_click_action: TrayClickAction::Show,
+            breathing_guide: false,
+            emoji_spawn_rate: 0.1,
+            emoji_speed_range: [100.0, 250.0],
+            emojis_above_ui: false,
+            rest_end_action: RestEndAction::Minimize,
+            rest_window_mode: RestWindowMode::Fullscreen,
+            rest_count_direction: RestCountDirection::Down,
+            open_ended_rest: false,
+            intensity: Intensity::Normal,
+            skip_nag_threshold: 3,
+            skip_cooldown_secs: 0,
+            skip_undo_secs: 5,
+            snooze_minutes: 5,
+            max_snoozes: 2,
+            paused_nudge_minutes: 0,
+            current_task: String::new(),
+            task_queue: vec![],
+            ask_session_note: false,
+            celebrate_work_done: false,
+            require_break_ack: false,
+            hold_to_skip: false,
+            smooth_transitions: false,
+            presets: vec![
+                Preset::new("标准", 25, 5),
+                Preset::new("深度专注", 50, 10),
+                Preset::new("短番茄", 15, 3),
+            ],
+            overlay_click_through: false,
+            overlay_borderless: false,
+            mini_overlay: false,
+            mini_overlay_pos: None,
+            sound_enabled: false,
+            reminders: default_reminders(),
+            suppress_during_fullscreen: false,
+            daily_work_cap_minutes: 0,
+            stats_autosave_secs: 60,
+            water_goal_cups: 0,
+            cap_forces_long_rest: false,
+            tooltip_show_stats: false,
+            tooltip_template: DEFAULT_TOOLTIP_TEMPLATE.to_string(),
+            time_format_24h: true,
+            show_next_task: true,
+            idle_repaint_pause: false,
+            minimize_to_taskbar: false,
+            allow_maximize: false,
+            allow_resize: true,
+            auto_minimize_on_work: false,
+            auto_minimize_delay_secs: 3,
+            break_sound: BUILTIN_SOUNDS[0].0.to_string(),
+            work_start_sound: String::new(),
+            rest_end_sound: String::new(),
+            sounds_dir: String::new(),
+            audio_device: None,
+        }
+    }
+}
+
+// 休息蒙层显示在哪个显示器上，Index 按显示器从左到右排序
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub(crate) enum OverlayMonitor {
+    Primary,
+    Current,
+    Index(usize),
+}
+
+impl OverlayMonitor {
+    pub(crate) fn label(&self) -> String {
+        match self {
+            OverlayMonitor::Primary => "主显示器".to_string(),
+            OverlayMonitor::Current => "窗口所在显示器".to_string(),
+            OverlayMonitor::Index(i) => format!("显示器 {}", i + 1),
+        }
+    }
+}
+
+// 托盘图标左键点击的行为
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub(crate) enum TrayClickAction {
+    Show,
+    Toggle,
+    PauseResume,
+}
+
+impl TrayClickAction {
+    pub(crate) const ALL: [TrayClickAction; 3] = [TrayClickAction::Show, TrayClickAction::Toggle, TrayClickAction::PauseResume];
+
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            TrayClickAction::Show => "显示窗口",
+            TrayClickAction::Toggle => "显示/隐藏窗口",
+            TrayClickAction::PauseResume => "暂停/继续",
+        }
+    }
+}
+
+// 各状态的强调色 (sRGB)，用于倒计时文字和托盘图标
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(default)]
+pub(crate) struct AccentColors {
+    pub(crate) working: [u8; 3],
+    pub(crate) resting: [u8; 3],
+    pub(crate) paused: [u8; 3], // 暂停和未开始共用
+}
+
+impl Default for AccentColors {
+    fn default() -> Self {
+        Self { working: [200, 80, 80], resting: [80, 180, 80], paused: [160, 160, 160] }
+    }
+}
+
+impl AccentColors {
+    pub(crate) fn for_state(&self, state: AppState) -> [u8; 3] {
+        match state {
+            AppState::Working => self.working,
+            AppState::Resting => self.resting,
+            AppState::Paused | AppState::Ready => self.paused,
+        }
+    }
+
+    // 主界面记录点的颜色: 完成的专注固定绿色，休息用暂停色，避免和默认的休息绿混在一起
+    pub(crate) fn session_dot(&self, kind: SessionKind) -> [u8; 3] {
+        match kind {
+            SessionKind::Work => WORK_DOT_COLOR,
+            SessionKind::Rest => self.paused,
+        }
+    }
+}
+
+pub(crate) const WORK_DOT_COLOR: [u8; 3] = [80, 180, 90];
+
+// 一组专注/休息时长；当前时长与哪个预设一致，哪个就是当前预设
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub(crate) struct Preset {
+    name: String,
+    work_minutes: u64,
+    rest_minutes: u64,
+}
+
+impl Preset {
+    pub(crate) fn new(name: &str, work_minutes: u64, rest_minutes: u64) -> Self {
+        Self { name: name.to_string(), work_minutes, rest_minutes }
+    }
+
+    pub(crate) fn label(&self) -> String {
+        format!("{} ({}/{})", self.name, self.work_minutes, self.rest_minutes)
+    }
+}
+
+// 健康提醒类型：开启后每次短休息按顺序轮换到下一个开启的类型
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub(crate) enum ReminderType {
+    Water,
+    Posture,
+    Eyes,
+    Stretch,
+}
+
+impl ReminderType {
+    pub(crate) fn icon(&self) -> &'static str {
+        match self {
+            ReminderType::Water => "💧",
+            ReminderType::Posture => "🪑",
+            ReminderType::Eyes => "👀",
+            ReminderType::Stretch => "🤸",
+        }
+    }
+
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            ReminderType::Water => "喝水",
+            ReminderType::Posture => "调整坐姿",
+            ReminderType::Eyes => "放松眼睛",
+            ReminderType::Stretch => "伸展身体",
+        }
+    }
+
+    pub(crate) fn message(&self) -> &'static str {
+        match self {
+            ReminderType::Water => "起来倒一杯水，慢慢喝完",
+            ReminderType::Posture => "背部挺直，双脚平放，屏幕与视线平齐",
+            ReminderType::Eyes => "看向 6 米外的远处 20 秒，多眨眨眼",
+            ReminderType::Stretch => "站起来活动肩颈、手腕和腰背",
+        }
+    }
+}
+
+// minutes 为 0 时使用普通休息时长
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub(crate) struct ReminderSetting {
+    pub(crate) kind: ReminderType,
+    pub(crate) enabled: bool,
+    pub(crate) minutes: u64,
+}
+
+pub(crate) fn default_reminders() -> Vec<ReminderSetting> {
+    [ReminderType::Water, ReminderType::Posture, ReminderType::Eyes, ReminderType::Stretch]
+        .into_iter()
+        .map(|kind| ReminderSetting { kind, enabled: false, minutes: 0 })
+        .collect()
+}
+
+// 从 start 开始 (含) 找下一个开启的提醒，到末尾后回到开头；全部关闭时返回 None
+pub(crate) fn next_reminder(reminders: &[ReminderSetting], start: usize) -> Option<usize> {
+    let len = reminders.len();
+    (0..len).map(|offset| (start + offset) % len).find(|&i| reminders[i].enabled)
+}
+
+// 休息结束 (倒计时走完) 后的行为；手动跳过休息始终按 Minimize 处理。
+// AutoStartNext 就是"自动开始下一个番茄"，没有单独的开关；非工作时间内不会自动开始
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub(crate) enum RestEndAction {
+    Minimize,      // 最小化并等待手动开始 (原来的行为)
+    ShowAndWait,   // 弹出主窗口等待手动开始
+    AutoStartNext, // 最小化并直接开始下一个番茄
+}
+
+impl RestEndAction {
+    pub(crate) const ALL: [RestEndAction; 3] = [RestEndAction::Minimize, RestEndAction::ShowAndWait, RestEndAction::AutoStartNext];
+
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            RestEndAction::Minimize => "最小化，等待开始",
+            RestEndAction::ShowAndWait => "显示窗口，等待开始",
+            RestEndAction::AutoStartNext => "自动开始下一个番茄",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub(crate) enum RestWindowMode {
+    Fullscreen, // 全屏蒙层 (原来的行为)
+    Windowed,   // 居中的普通窗口，只显示倒计时和跳过按钮，适合大屏幕上的短休息
+}
+
+impl RestWindowMode {
+    pub(crate) const ALL: [RestWindowMode; 2] = [RestWindowMode::Fullscreen, RestWindowMode::Windowed];
+
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            RestWindowMode::Fullscreen => "全屏",
+            RestWindowMode::Windowed => "窗口",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub(crate) enum RestCountDirection {
+    Down, // 显示剩余时间
+    Up,   // 显示已经休息了多久
+}
+
+impl RestCountDirection {
+    pub(crate) const ALL: [RestCountDirection; 2] = [RestCountDirection::Down, RestCountDirection::Up];
+
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            RestCountDirection::Down => "倒计时",
+            RestCountDirection::Up => "正计时",
+        }
+    }
+}
+
+// 运行时叠加在细分设置之上: Gentle 只显示角落小窗，Strict 全屏置顶且不可跳过
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub(crate) enum Intensity {
+    Gentle, // 只弹通知小窗，可以跳过
+    Normal, // 按其它设置显示蒙层 (原来的行为)
+    Strict, // 全屏蒙层，抢占焦点，不能跳过或暂停
+}
+
+impl Intensity {
+    pub(crate) const ALL: [Intensity; 3] = [Intensity::Gentle, Intensity::Normal, Intensity::Strict];
+
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            Intensity::Gentle => "温和 (仅通知)",
+            Intensity::Normal => "普通",
+            Intensity::Strict => "严格 (不可跳过)",
+        }
+    }
+}
+
+// 单日工作时间段，start/end 为当天的分钟数；end < start 表示跨过午夜
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub(crate) struct DaySchedule {
+    pub(crate) enabled: bool,
+    pub(crate) start: u32,
+    pub(crate) end: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub(crate) struct WorkSchedule {
+    pub(crate) enabled: bool,
+    pub(crate) days: [DaySchedule; 7], // 周一到周日
+}
+
+impl Default for WorkSchedule {
+    fn default() -> Self {
+        let day = |enabled| DaySchedule { enabled, start: 9 * 60, end: 18 * 60 };
+        Self {
+            enabled: false,
+            days: [day(true), day(true), day(true), day(true), day(true), day(false), day(false)],
+        }
+    }
+}
+
+impl WorkSchedule {
+    pub(crate) const DAY_NAMES: [&'static str; 7] = ["周一", "周二", "周三", "周四", "周五", "周六", "周日"];
+
+    // 未启用时间表时永远视为工作时间
+    pub(crate) fn is_off_hours(&self, now: chrono::NaiveDateTime) -> bool {
+        use chrono::{Datelike, Timelike};
+        if !self.enabled {
+            return false;
+        }
+        let day = &self.days[now.weekday().num_days_from_monday() as usize];
+        let minute = now.hour() * 60 + now.minute();
+        !(day.enabled && minute_in_window(minute, day.start, day.end))
+    }
+}
+
+// [start, end) 时间段，end < start 表示跨过午夜
+pub(crate) fn minute_in_window(minute: u32, start: u32, end: u32) -> bool {
+    if start <= end {
+        minute >= start && minute < end
+    } else {
+        minute >= start || minute < end
+    }
+}
+
+impl AppConfig {
+    pub(crate) fn set_work_minutes(&mut self, minutes: u64) {
+        self.work_minutes = minutes.clamp(1, MAX_SESSION_MINUTES);
+    }
+
+    pub(crate) fn set_rest_minutes(&mut self, minutes: u64) {
+        self.rest_minutes = minutes.clamp(1, MAX_SESSION_MINUTES);
+    }
+
+    pub(crate) fn set_long_rest_minutes(&mut self, minutes: u64) {
+        self.long_rest_minutes = minutes.clamp(1, MAX_SESSION_MINUTES);
+    }
+
+    pub(crate) fn set_emoji_spawn_rate(&mut self, rate: f32) {
+        // NaN 视为关闭
+        self.emoji_spawn_rate = if rate.is_nan() { 0.0 } else { rate.clamp(0.0, 1.0) };
+    }
+
+    pub(crate) fn set_emoji_speed_range(&mut self, [min, max]: [f32; 2]) {
+        let clamp = |v: f32| if v.is_nan() { *EMOJI_SPEED_BOUNDS.start() } else { v.clamp(*EMOJI_SPEED_BOUNDS.start(), *EMOJI_SPEED_BOUNDS.end()) };
+        let (min, max) = (clamp(min), clamp(max));
+        self.emoji_speed_range = [min.min(max), min.max(max)];
+    }
+
+    pub(crate) fn is_quiet_hours(&self, now: chrono::NaiveDateTime) -> bool {
+        use chrono::Timelike;
+        self.quiet_hours_enabled
+            && minute_in_window(now.hour() * 60 + now.minute(), self.quiet_hours_start, self.quiet_hours_end)
+    }
+
+    pub(crate) fn active_preset(&self) -> Option<usize> {
+        self.presets.iter().position(|p| p.work_minutes == self.work_minutes && p.rest_minutes == self.rest_minutes)
+    }
+
+    // 严格模式总是全屏；窗口模式优先于无边框蒙层
+    pub(crate) fn overlay_style(&self) -> OverlayStyle {
+        match (self.rest_window_mode, self.intensity) {
+            (RestWindowMode::Windowed, intensity) if intensity != Intensity::Strict => OverlayStyle::Windowed,
+            _ if self.overlay_borderless => OverlayStyle::Borderless,
+            _ => OverlayStyle::Fullscreen,
+        }
+    }
+
+    pub(crate) fn apply_preset(&mut self, index: usize) -> bool {
+        let Some(preset) = self.presets.get(index).cloned() else { return false };
+        self.set_work_minutes(preset.work_minutes);
+        self.set_rest_minutes(preset.rest_minutes);
+        true
+    }
+
+    pub(crate) fn resolved_sounds_dir(&self) -> Option<std::path::PathBuf> {
+        let dir = self.sounds_dir.trim();
+        if !dir.is_empty() {
+            return Some(std::path::PathBuf::from(dir));
+        }
+        Self::config_path().and_then(|p| Some(p.parent()?.join("sounds")))
+    }
+
+    pub(crate) fn config_path() -> Option<std::path::PathBuf> {
+        // 测试中不读写真实的用户配置
+        if cfg!(test) || MEMORY_ONLY.load(Ordering::Relaxed) {
+            return None;
+        }
+        Self::resolved_config_path()
+    }
+
+    pub(crate) fn resolved_config_path() -> Option<std::path::PathBuf> {
+        resolve_config_path(
+            config_arg(std::env::args()),
+            std::env::var_os(CONFIG_ENV_VAR).map(std::path::PathBuf::from),
+            portable_dir().cloned(),
+            dirs::config_dir(),
+        )
+    }
+
+    // 读取失败或文件不存在时使用默认配置
+    pub(crate) fn load(warnings: &mut Vec<String>) -> Self {
+        let Some(path) = Self::config_path() else { return Self::default() };
+        match std::fs::read_to_string(&path) {
+            Ok(text) => Self::parse_as(&text, ConfigFormat::for_path(&path)).unwrap_or_else(|e| {
+                println!("配置文件解析失败，使用默认配置: {}", e);
+                warnings.push(format!("配置文件 {} 无法读取 ({})，已使用默认设置", path.display(), e));
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    // 配置文件和导入的设置文件共用: 按版本号迁移，拒绝更新版本程序写出的文件
+    // TOML 先转成同样的 JSON 值，两种格式走同一套迁移和校验
+    pub(crate) fn parse_as(text: &str, format: ConfigFormat) -> Result<Self, String> {
+        let value: serde_json::Value = match format {
+            ConfigFormat::Json => serde_json::from_str(text).map_err(|e| format!("不是有效的 JSON: {}", e))?,
+            ConfigFormat::Toml => toml::from_str(text).map_err(|e| format!("不是有效的 TOML: {}", e))?,
+        };
+        if !value.is_object() {
+            return Err("文件内容不是设置".to_string());
+        }
+        // 加入版本号之前的文件没有这个字段，视为 0
+        let version = value.get("config_version").and_then(serde_json::Value::as_u64).unwrap_or(0);
+        if version > u64::from(CONFIG_VERSION) {
+            return Err(format!("设置来自更新的版本 (格式 v{}，当前支持 v{})，请先升级程序", version, CONFIG_VERSION));
+        }
+        let mut config: Self = serde_json::from_value(value).map_err(|e| format!("设置内容不兼容: {}", e))?;
+        // v0 -> v1: 字段没有变化，只补上版本号；以后改字段含义时在这里按版本转换
+        config.config_version = CONFIG_VERSION;
+        // 手动改过的配置文件也要落在合法范围内
+        config.set_work_minutes(config.work_minutes);
+        config.set_rest_minutes(config.rest_minutes);
+        config.set_long_rest_minutes(config.long_rest_minutes);
+        config.set_emoji_spawn_rate(config.emoji_spawn_rate);
+        config.set_emoji_speed_range(config.emoji_speed_range);
+        Ok(config)
+    }
+
+    pub(crate) fn save(&self) {
+        let Some(path) = Self::config_path() else { return };
+        if let Some(dir) = path.parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        match self.to_text(ConfigFormat::for_path(&path)) {
+            Ok(text) => {
+                if let Err(e) = write_atomic(&path, &text) {
+                    report_save_error(format!("保存配置失败: {}", e));
+                }
+            }
+            Err(e) => println!("序列化配置失败: {}", e),
+        }
+    }
+
+    pub(crate) fn to_text(&self, format: ConfigFormat) -> Result<String, String> {
+        match format {
+            ConfigFormat::Json => serde_json::to_string_pretty(self).map_err(|e| e.to_string()),
+            ConfigFormat::Toml => toml::to_string_pretty(self).map_err(|e| e.to_string()),
+        }
+    }
+}
+
+// 启动时确认配置目录可用 (受限的机器上可能拿不到、建不了，或者目录在但只读)，返回给用户看的原因
+pub(crate) fn check_config_dir(path: Option<&std::path::Path>) -> Result<(), String> {
+    let Some(path) = path else { return Err("找不到系统的配置目录".to_string()) };
+    let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(std::path::Path::new("."));
+    std::fs::create_dir_all(dir).map_err(|e| format!("无法创建配置目录 {}: {}", dir.display(), e))?;
+    // 只读目录 create_dir_all 也返回 Ok，实际写一个临时文件才知道
+    let probe = dir.join(format!(".remindrest-write-test-{}", std::process::id()));
+    std::fs::write(&probe, b"").map_err(|e| format!("配置目录 {} 无法写入: {}", dir.display(), e))?;
+    let _ = std::fs::remove_file(&probe);
+    Ok(())
+}
+
+pub(crate) const CONFIG_ENV_VAR: &str = "REMINDREST_CONFIG";
+pub(crate) const CONFIG_VERSION: u32 = 1;
+pub(crate) const SETTINGS_EXPORT_NAME: &str = "remindrest-settings.json";
+
+// 从命令行参数中取出 `--config <path>` 或 `--config=<path>`
+pub(crate) fn config_arg(args: impl IntoIterator<Item = String>) -> Option<std::path::PathBuf> {
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--config" {
+            return args.next().map(std::path::PathBuf::from);
+        }
+        if let Some(path) = arg.strip_prefix("--config=") {
+            return Some(std::path::PathBuf::from(path));
+        }
+    }
+    None
+}
+
+// 命令行优先于环境变量，然后是便携模式的程序目录，都没有时用系统配置目录；
+// 指向已有目录时在其中放 config.json。统计和记录文件都放在配置文件旁边
+pub(crate) fn resolve_config_path(
+    cli: Option<std::path::PathBuf>,
+    env: Option<std::path::PathBuf>,
+    portable: Option<std::path::PathBuf>,
+    config_dir: Option<std::path::PathBuf>,
+) -> Option<std::path::PathBuf> {
+    match cli.or(env).filter(|p| !p.as_os_str().is_empty()) {
+        Some(path) if path.is_dir() => Some(config_file_in(&path)),
+        Some(path) => Some(path),
+        None if portable.is_some() => portable.map(|dir| config_file_in(&dir)),
+        None => config_dir.map(|dir| config_file_in(&dir.join("RestReminder"))),
+    }
+}
+
+// 目录里只有手写的 config.toml 时用它，否则用 config.json
+pub(crate) fn config_file_in(dir: &std::path::Path) -> std::path::PathBuf {
+    let (json, toml) = (dir.join("config.json"), dir.join("config.toml"));
+    if toml.is_file() && !json.exists() { toml } else { json }
+}
+
+// 配置和导出的设置文件按扩展名决定格式，.toml 用 TOML，其它都是 JSON
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) enum ConfigFormat {
+    Json,
+    Toml,
+}
+
+impl ConfigFormat {
+    pub(crate) fn for_path(path: &std::path::Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("toml") => ConfigFormat::Toml,
+            _ => ConfigFormat::Json,
+        }
+    }
+}
+
+pub(crate) const PORTABLE_MARKER: &str = "portable.txt";
+
+// 便携模式 (U 盘使用): 带 --portable 参数启动，或程序旁边有 portable.txt。
+// 只在第一次用到时检测一次；程序目录不可写时退回系统配置目录
+pub(crate) fn portable_dir() -> Option<&'static std::path::PathBuf> {
+    static PORTABLE_DIR: std::sync::OnceLock<Option<std::path::PathBuf>> = std::sync::OnceLock::new();
+    PORTABLE_DIR
+        .get_or_init(|| {
+            let exe_dir = std::env::current_exe().ok()?.parent()?.to_path_buf();
+            let flag = std::env::args().any(|arg| arg == "--portable");
+            detect_portable(flag, exe_dir)
+        })
+        .as_ref()
+}
+
+pub(crate) fn detect_portable(flag: bool, exe_dir: std::path::PathBuf) -> Option<std::path::PathBuf> {
+    if !flag && !exe_dir.join(PORTABLE_MARKER).is_file() {
+        return None;
+    }
+    // 写一个探测文件确认目录可写 (只读的 U 盘、Program Files 等)
+    let probe = exe_dir.join(".remindrest-write-test");
+    match std::fs::write(&probe, b"") {
+        Ok(()) => {
+            let _ = std::fs::remove_file(&probe);
+            println!("便携模式: 数据保存在 {}", exe_dir.display());
+            Some(exe_dir)
+        }
+        Err(e) => {
+            println!("便携模式: 程序目录 {} 不可写 ({})，改用系统配置目录", exe_dir.display(), e);
+            None
+        }
+    }
+}
+
+// 先写同目录下的临时文件并刷到磁盘，再重命名覆盖目标；写到一半被杀掉时目标文件保持旧内容
+pub(crate) fn write_atomic(path: &std::path::Path, contents: &str) -> std::io::Result<()> {
+    use std::io::Write;
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp = path.with_file_name(tmp_name);
+    let mut file = std::fs::File::create(&tmp)?;
+    file.write_all(contents.as_bytes())?;
+    file.sync_all()?;
+    drop(file);
+    std::fs::rename(&tmp, path)
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum ParseError {
+    Empty,
+    Negative,
+    Invalid,
+    NotWholeMinutes,
+    InvalidSeconds,
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            ParseError::Empty => "请输入时长",
+            ParseError::Negative => "时长不能为负数",
+            ParseError::Invalid => "无法识别的时长，请输入 25、25:30 或 25.5 (分钟)",
+            ParseError::NotWholeMinutes => "这一项只能填整分钟，例如 25",
+            ParseError::InvalidSeconds => "无法识别的秒数，请输入 90 或 1:30 (分:秒)",
+        };
+        f.write_str(text)
+    }
+}
+
+// 时长输入: "25" 分钟、"25:30" 分:秒 (秒数小于 60)、"25.5" 小数分钟；不接受负数、指数和 inf 等写法
+pub(crate) fn parse_duration(text: &str) -> Result<Duration, ParseError> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(ParseError::Empty);
+    }
+    if text.starts_with('-') {
+        return Err(ParseError::Negative);
+    }
+    let digits = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+    if let Some((minutes, seconds)) = text.split_once(':') {
+        if !digits(minutes) || !digits(seconds) {
+            return Err(ParseError::Invalid);
+        }
+        let minutes: u64 = minutes.parse().map_err(|_| ParseError::Invalid)?;
+        let seconds: u64 = seconds.parse().map_err(|_| ParseError::Invalid)?;
+        if seconds >= 60 {
+            return Err(ParseError::Invalid);
+        }
+        let total = minutes.checked_mul(60).and_then(|s| s.checked_add(seconds)).ok_or(ParseError::Invalid)?;
+        return Ok(Duration::from_secs(total));
+    }
+    let (whole, fraction) = text.split_once('.').unwrap_or((text, ""));
+    let valid = match (whole.is_empty(), fraction.is_empty()) {
+        (true, true) => false,
+        (true, false) => digits(fraction),
+        (false, true) => digits(whole) && !text.ends_with('.'),
+        (false, false) => digits(whole) && digits(fraction),
+    };
+    if !valid {
+        return Err(ParseError::Invalid);
+    }
+    let minutes: f64 = text.parse().map_err(|_| ParseError::Invalid)?;
+    Duration::try_from_secs_f64(minutes * 60.0).map_err(|_| ParseError::Invalid)
+}
+
+// 只存整分钟的设置用这个: "25:30"、"25.5" 这类不是整分钟的输入直接报错，不悄悄舍入
+pub(crate) fn parse_minutes(text: &str) -> Result<u64, ParseError> {
+    let duration = parse_duration(text)?;
+    if duration.subsec_nanos() != 0 || !duration.as_secs().is_multiple_of(60) {
+        return Err(ParseError::NotWholeMinutes);
+    }
+    Ok(duration.as_secs() / 60)
+}
+
+// 以秒为单位的设置用这个: "90" 是 90 秒，"1:30" 同 parse_duration 按分:秒解析；不接受小数
+pub(crate) fn parse_seconds(text: &str) -> Result<u64, ParseError> {
+    let text = text.trim();
+    if text.contains(':') || text.is_empty() || text.starts_with('-') {
+        return parse_duration(text).map(|d| d.as_secs()).map_err(|e| match e {
+            ParseError::Invalid => ParseError::InvalidSeconds,
+            e => e,
+        });
+    }
+    if !text.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(ParseError::InvalidSeconds);
+    }
+    text.parse().map_err(|_| ParseError::InvalidSeconds)
+}
+
+// 配置里的时长以分钟为单位，四舍五入到整分钟
+pub(crate) fn duration_to_minutes(duration: Duration) -> u64 {
+    (duration.as_secs() + 30) / 60
+}
+
+// 秒数在输入框里的写法，整分钟显示为 "5"，否则为 "1:30"
+pub(crate) fn duration_input_text(secs: u64) -> String {
+    if secs.is_multiple_of(60) { (secs / 60).to_string() } else { format!("{}:{:02}", secs / 60, secs % 60) }
+}
+
+// 分钟数换算为 Duration，乘法饱和避免溢出
+pub(crate) fn minutes_to_duration(minutes: u64) -> Duration {
+    Duration::from_secs(minutes.saturating_mul(60))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn schedule_off_hours() {
+        let at = |date: &str| chrono::NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M").unwrap();
+        let mut schedule = WorkSchedule::default();
+        // 2024-01-01 是周一
+        assert!(!schedule.is_off_hours(at("2024-01-01 03:00")), "未启用时不限制");
+
+        schedule.enabled = true;
+        assert!(schedule.is_off_hours(at("2024-01-01 08:59")));
+        assert!(!schedule.is_off_hours(at("2024-01-01 09:00")));
+        assert!(!schedule.is_off_hours(at("2024-01-01 17:59")));
+        assert!(schedule.is_off_hours(at("2024-01-01 18:00")));
+        assert!(schedule.is_off_hours(at("2024-01-06 10:00")), "周六默认不工作");
+
+        schedule.days[0] = DaySchedule { enabled: true, start: 22 * 60, end: 2 * 60 };
+        assert!(!schedule.is_off_hours(at("2024-01-01 23:30")));
+        assert!(!schedule.is_off_hours(at("2024-01-01 01:00")));
+        assert!(schedule.is_off_hours(at("2024-01-01 12:00")));
+    }
+
+    #[test]
+    fn durations_are_bounded() {
+        let mut config = AppConfig::default();
+        config.set_work_minutes(MAX_SESSION_MINUTES);
+        assert_eq!(config.work_minutes, MAX_SESSION_MINUTES);
+        config.set_work_minutes(MAX_SESSION_MINUTES + 1);
+        assert_eq!(config.work_minutes, MAX_SESSION_MINUTES);
+        config.set_rest_minutes(100_000);
+        assert_eq!(config.rest_minutes, MAX_SESSION_MINUTES);
+        config.set_rest_minutes(0);
+        assert_eq!(config.rest_minutes, 1);
+
+        assert_eq!(minutes_to_duration(u64::MAX), Duration::from_secs(u64::MAX));
+        assert_eq!(minutes_to_duration(25), Duration::from_secs(1500));
+    }
+
+    #[test]
+    fn config_path_override_order() {
+        use std::path::PathBuf;
+        let args = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(config_arg(args(&["app", "--config", "a.json"])), Some(PathBuf::from("a.json")));
+        assert_eq!(config_arg(args(&["app", "--config=b.json"])), Some(PathBuf::from("b.json")));
+        assert_eq!(config_arg(args(&["app"])), None);
+
+        let sys = Some(PathBuf::from("sys"));
+        let cli = Some(PathBuf::from("cli.json"));
+        let env = Some(PathBuf::from("env.json"));
+        assert_eq!(resolve_config_path(cli.clone(), env.clone(), None, sys.clone()), cli);
+        assert_eq!(resolve_config_path(None, env.clone(), None, sys.clone()), env);
+        assert_eq!(
+            resolve_config_path(None, Some(PathBuf::new()), None, sys.clone()),
+            Some(PathBuf::from("sys").join("RestReminder").join("config.json"))
+        );
+        let dir = std::env::temp_dir();
+        assert_eq!(resolve_config_path(Some(dir.clone()), None, None, None), Some(dir.join("config.json")));
+    }
+
+    #[test]
+    fn accent_colors_follow_state() {
+        let colors = AccentColors { working: [1, 2, 3], ..Default::default() };
+        assert_eq!(colors.for_state(AppState::Working), [1, 2, 3]);
+        assert_eq!(colors.for_state(AppState::Ready), colors.for_state(AppState::Paused));
+        let defaults = AccentColors::default();
+        assert_ne!(defaults.session_dot(SessionKind::Work), defaults.session_dot(SessionKind::Rest), "专注和休息的记录点要分得清");
+        let icon = tray_icon_rgba([1, 2, 3]);
+        let center = (32 * 64 + 32) * 4;
+        assert_eq!(&icon[center..center + 4], &[1, 2, 3, 255]);
+        assert_eq!(icon[3], 0, "角落透明");
+    }
+
+    #[test]
+    fn interrupted_write_keeps_old_file() {
+        let dir = std::env::temp_dir().join(format!("remindrest-atomic-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.json");
+        write_atomic(&path, r#"{"work_minutes": 40}"#).unwrap();
+
+        // 上次写临时文件时被中断留下的残缺文件: 下次保存照常覆盖它，正式文件得到完整的新内容
+        std::fs::write(dir.join("config.json.tmp"), r#"{"work_min"#).unwrap();
+        write_atomic(&path, r#"{"work_minutes": 45}"#).unwrap();
+        let config: AppConfig = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(config.work_minutes, 45);
+        assert!(!dir.join("config.json.tmp").exists());
+
+        // 临时文件无法写入 (被占用为目录) 时保存失败，旧文件原样保留
+        std::fs::create_dir(dir.join("config.json.tmp")).unwrap();
+        assert!(write_atomic(&path, "{").is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), r#"{"work_minutes": 45}"#);
+        std::fs::remove_dir(dir.join("config.json.tmp")).unwrap();
+
+        write_atomic(&path, "{}").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "{}");
+        assert!(!dir.join("config.json.tmp").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn parse_duration_accepted_forms() {
+        let secs = |text| parse_duration(text).map(|d| d.as_secs());
+        assert_eq!(secs("25"), Ok(25 * 60));
+        assert_eq!(secs(" 25 "), Ok(25 * 60));
+        assert_eq!(secs("0"), Ok(0));
+        assert_eq!(secs("25:30"), Ok(25 * 60 + 30));
+        assert_eq!(secs("0:45"), Ok(45));
+        assert_eq!(secs("1:05"), Ok(65));
+        assert_eq!(secs("25.5"), Ok(25 * 60 + 30));
+        assert_eq!(secs("0.25"), Ok(15));
+        assert_eq!(secs(".5"), Ok(30));
+        assert_eq!(secs("007"), Ok(7 * 60));
+        assert_eq!(secs("1440"), Ok(1440 * 60));
+    }
+
+    #[test]
+    fn parse_duration_rejected_forms() {
+        use ParseError::*;
+        for (text, error) in [
+            ("", Empty),
+            ("   ", Empty),
+            ("-5", Negative),
+            ("-0:30", Negative),
+            ("abc", Invalid),
+            ("25m", Invalid),
+            ("25.", Invalid),
+            (".", Invalid),
+            ("1.2.3", Invalid),
+            ("1e3", Invalid),
+            ("inf", Invalid),
+            ("NaN", Invalid),
+            ("+5", Invalid),
+            ("25:60", Invalid),
+            ("25:", Invalid),
+            (":30", Invalid),
+            ("1:2:3", Invalid),
+            ("1.5:30", Invalid),
+            ("2 5", Invalid),
+            ("99999999999999999999", Invalid),
+            ("999999999999999999:00", Invalid),
+        ] {
+            assert_eq!(parse_duration(text), Err(error), "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn minute_fields_reject_partial_minutes() {
+        assert_eq!(parse_minutes("25"), Ok(25));
+        assert_eq!(parse_minutes("25:00"), Ok(25));
+        assert_eq!(parse_minutes("2.0"), Ok(2));
+        assert_eq!(parse_minutes("25:29"), Err(ParseError::NotWholeMinutes));
+        assert_eq!(parse_minutes("0:20"), Err(ParseError::NotWholeMinutes));
+        assert_eq!(parse_minutes("25.5"), Err(ParseError::NotWholeMinutes));
+        assert_eq!(parse_minutes("abc"), Err(ParseError::Invalid), "其余错误照常报告");
+    }
+
+    #[test]
+    fn second_fields_report_errors() {
+        assert_eq!(parse_seconds("90"), Ok(90));
+        assert_eq!(parse_seconds(" 0 "), Ok(0));
+        assert_eq!(parse_seconds("1:30"), Ok(90));
+        assert_eq!(parse_seconds(""), Err(ParseError::Empty));
+        assert_eq!(parse_seconds("-5"), Err(ParseError::Negative));
+        for text in ["abc", "1.5", "1e3", "+5", "1:60", "99999999999999999999"] {
+            assert_eq!(parse_seconds(text), Err(ParseError::InvalidSeconds), "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn duration_minutes_round_trip() {
+        assert_eq!(duration_to_minutes(Duration::from_secs(25 * 60 + 29)), 25);
+        assert_eq!(duration_to_minutes(Duration::from_secs(25 * 60 + 30)), 26);
+        assert_eq!(duration_input_text(300), "5");
+        assert_eq!(duration_input_text(90), "1:30");
+        assert_eq!(parse_duration(&duration_input_text(90)).map(|d| d.as_secs()), Ok(90));
+    }
+
+    #[test]
+    fn portable_mode_keeps_data_beside_exe() {
+        use std::path::PathBuf;
+        let exe_dir = std::env::temp_dir().join(format!("remindrest-portable-{}", std::process::id()));
+        std::fs::create_dir_all(&exe_dir).unwrap();
+        assert_eq!(detect_portable(false, exe_dir.clone()), None, "没有参数也没有标记文件");
+        assert_eq!(detect_portable(true, exe_dir.clone()), Some(exe_dir.clone()));
+        std::fs::write(exe_dir.join(PORTABLE_MARKER), b"").unwrap();
+        assert_eq!(detect_portable(false, exe_dir.clone()), Some(exe_dir.clone()));
+        assert_eq!(detect_portable(true, exe_dir.join("missing")), None, "不可写时退回系统目录");
+        std::fs::remove_dir_all(&exe_dir).unwrap();
+
+        let portable = Some(PathBuf::from("usb"));
+        let sys = Some(PathBuf::from("sys"));
+        assert_eq!(resolve_config_path(None, None, portable.clone(), sys.clone()), Some(PathBuf::from("usb").join("config.json")));
+        let cli = Some(PathBuf::from("cli.json"));
+        assert_eq!(resolve_config_path(cli.clone(), None, portable, sys), cli, "命令行仍然优先");
+    }
+
+    #[test]
+    fn default_config_round_trips_through_serde() {
+        let config = AppConfig::default();
+        let text = serde_json::to_string(&config).unwrap();
+        let back: AppConfig = serde_json::from_str(&text).unwrap();
+        assert_eq!(back, config);
+        // 改过的值也要原样读回
+        let config = AppConfig { work_minutes: 50, webhook_url: Some("http://localhost/hook".to_string()), ..Default::default() };
+        let back: AppConfig = serde_json::from_str(&serde_json::to_string_pretty(&config).unwrap()).unwrap();
+        assert_eq!(back, config);
+    }
+
+    #[test]
+    fn old_config_fills_missing_fields_with_defaults() {
+        // 早期版本只保存了这几项
+        let old = r#"{"work_minutes": 45, "rest_minutes": 10, "auto_hide_when_idle_secs": 0, "high_contrast": true}"#;
+        let config: AppConfig = serde_json::from_str(old).unwrap();
+        let expected = AppConfig { work_minutes: 45, rest_minutes: 10, high_contrast: true, ..Default::default() };
+        assert_eq!(config, expected);
+        let empty: AppConfig = serde_json::from_str("{}").unwrap();
+        assert_eq!(empty, AppConfig::default());
+    }
+
+    #[test]
+    fn event_sounds_default_to_none() {
+        let config = AppConfig::parse_as(r#"{"break_sound": "builtin:chime"}"#, ConfigFormat::Json).unwrap();
+        assert_eq!(config.break_sound, "builtin:chime", "原来的休息提示音沿用为开始休息的声音");
+        assert!(config.work_start_sound.is_empty());
+        assert!(config.rest_end_sound.is_empty());
+        assert_eq!(sound_label(&config.rest_end_sound), "无");
+    }
+
+    #[test]
+    fn unusable_config_dir_is_reported() {
+        assert!(check_config_dir(None).is_err());
+        let dir = std::env::temp_dir().join(format!("remindrest-dircheck-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        assert!(check_config_dir(Some(&dir.join("app").join("config.json"))).is_ok());
+        assert!(dir.join("app").is_dir());
+        // 父路径是个文件时建不了目录
+        std::fs::write(dir.join("file"), "").unwrap();
+        let err = check_config_dir(Some(&dir.join("file").join("config.json"))).unwrap_err();
+        assert!(err.contains("无法创建配置目录"), "{}", err);
+        assert_eq!(std::fs::read_dir(dir.join("app")).unwrap().count(), 0, "检查用的临时文件已删除");
+        let _ = std::fs::remove_dir_all(&dir);
+        // 目录存在但写不进去 (root 也写不了 /proc)
+        if cfg!(target_os = "linux") {
+            let err = check_config_dir(Some(std::path::Path::new("/proc/config.json"))).unwrap_err();
+            assert!(err.contains("无法写入"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn save_errors_are_reported_once() {
+        report_save_error("保存统计失败: 拒绝访问".to_string());
+        assert_eq!(take_save_error().as_deref(), Some("保存统计失败: 拒绝访问"));
+        assert_eq!(take_save_error(), None);
+    }
+
+    #[test]
+    fn toml_config_round_trips_losslessly() {
+        let config = AppConfig {
+            work_minutes: 50,
+            webhook_url: Some("http://localhost/hook".to_string()),
+            mini_overlay_pos: Some([10.0, 20.0]),
+            audio_device: None,
+            task_queue: vec!["写报告".to_string()],
+            ..Default::default()
+        };
+        let toml_text = config.to_text(ConfigFormat::Toml).unwrap();
+        let from_toml = AppConfig::parse_as(&toml_text, ConfigFormat::Toml).unwrap();
+        assert_eq!(from_toml, config);
+        let json_text = from_toml.to_text(ConfigFormat::Json).unwrap();
+        assert_eq!(AppConfig::parse_as(&json_text, ConfigFormat::Json).unwrap(), config, "TOML 和 JSON 互转不丢内容");
+
+        let partial = AppConfig::parse_as("work_minutes = 30\n", ConfigFormat::Toml).unwrap();
+        assert_eq!(partial.work_minutes, 30);
+        assert_eq!(partial.rest_minutes, AppConfig::default().rest_minutes);
+        assert!(AppConfig::parse_as("work_minutes = ", ConfigFormat::Toml).is_err());
+
+        assert_eq!(ConfigFormat::for_path(std::path::Path::new("a/Config.TOML")), ConfigFormat::Toml);
+        assert_eq!(ConfigFormat::for_path(std::path::Path::new("a/config.json")), ConfigFormat::Json);
+        let dir = std::env::temp_dir().join(format!("remindrest-toml-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        assert_eq!(config_file_in(&dir), dir.join("config.json"));
+        std::fs::write(dir.join("config.toml"), "").unwrap();
+        assert_eq!(config_file_in(&dir), dir.join("config.toml"), "只有 TOML 时用 TOML");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
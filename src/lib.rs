@@ -0,0 +1,37 @@
+//! 番茄钟休息提醒。main.rs 只调用 run()，逻辑都在这里的各个模块中
+
+mod config;
+mod platform;
+mod stats;
+mod timer;
+mod tray;
+mod ui;
+
+use config::*;
+use platform::*;
+use stats::*;
+use timer::*;
+use tray::*;
+use ui::*;
+
+// 程序入口: 读取窗口设置后交给 eframe 运行
+pub fn run() -> eframe::Result<()> {
+    // 这里只取窗口设置，读取时的提示在 new 里重新加载时显示
+    let config = AppConfig::load(&mut vec![]);
+    let options = eframe::NativeOptions {
+        viewport: main_viewport(&config),
+        ..Default::default()
+    };
+    eframe::run_native(
+        "番茄钟提醒",
+        options,
+        Box::new(|cc| match RestReminderApp::new(cc) {
+            Ok(app) => Ok(Box::new(app)),
+            Err(e) => {
+                println!("启动失败: {}", e);
+                show_fatal_error(&e.to_string());
+                Err(Box::new(e))
+            }
+        }),
+    )
+}
@@ -0,0 +1,718 @@
+//! 和操作系统打交道的部分: 注册表、窗口句柄、声音、外部命令等；非 Windows 平台是空实现
+
+use eframe::egui;
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(not(target_os = "linux"))]
+use std::time::Duration;
+
+#[cfg(target_os = "windows")]
+use winapi::shared::windef::HWND;
+#[cfg(target_os = "windows")]
+use winapi::um::winuser::{SetForegroundWindow, ShowWindow, SW_RESTORE, SW_SHOW, SW_SHOWNOACTIVATE};
+#[cfg(target_os = "windows")]
+use winreg::enums::*;
+#[cfg(target_os = "windows")]
+use winreg::RegKey;
+
+use crate::*;
+
+#[cfg(target_os = "windows")]
+pub(crate) fn attach_console() {
+    // 确保这里的路径是正确的
+    use winapi::um::consoleapi::AllocConsole;
+    unsafe {
+        let _ = AllocConsole();
+    }
+    println!("--- 控制台已附加，日志将显示在这里 ---");
+}
+
+#[cfg(not(target_os = "windows"))]
+pub(crate) fn attach_console() {}
+
+// 窗口是否已隐藏到托盘 (托盘线程直接显示窗口时也会清除)
+pub(crate) static WINDOW_HIDDEN: AtomicBool = AtomicBool::new(false);
+// 会话锁屏状态，由 Windows 会话通知线程写入
+pub(crate) static SESSION_LOCKED: AtomicBool = AtomicBool::new(false);
+// 托盘线程读取的 steal_focus_on_show 配置副本
+pub(crate) static STEAL_FOCUS_ON_SHOW: AtomicBool = AtomicBool::new(true);
+
+// 用于存储窗口句柄的全局变量
+#[cfg(target_os = "windows")]
+pub(crate) static WINDOW_HANDLE: std::sync::atomic::AtomicPtr<std::ffi::c_void> = std::sync::atomic::AtomicPtr::new(std::ptr::null_mut());
+
+// 内置提示音用正弦波合成，不需要随程序附带音频文件
+pub(crate) const BUILTIN_SOUNDS: [(&str, &str); 2] = [("builtin:ding", "叮 (内置)"), ("builtin:chime", "双音 (内置)")];
+
+pub(crate) fn sound_label(id: &str) -> String {
+    if id.is_empty() {
+        return "无".to_string();
+    }
+    BUILTIN_SOUNDS.iter().find(|(builtin, _)| *builtin == id).map_or_else(|| id.to_string(), |(_, label)| label.to_string())
+}
+
+// 文件夹中的 .wav/.mp3 文件名，按名称排序；文件夹不存在时返回空
+pub(crate) fn scan_sounds(dir: &std::path::Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(dir) else { return vec![] };
+    let mut sounds: Vec<String> = entries
+        .flatten()
+        .filter(|entry| entry.path().is_file())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| {
+            let ext = std::path::Path::new(name).extension().and_then(|e| e.to_str()).unwrap_or("");
+            ext.eq_ignore_ascii_case("wav") || ext.eq_ignore_ascii_case("mp3")
+        })
+        .collect();
+    sounds.sort();
+    sounds
+}
+
+// 显示器区域 (物理像素)
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) struct MonitorRect {
+    pub(crate) left: i32,
+    pub(crate) top: i32,
+    pub(crate) width: i32,
+    pub(crate) height: i32,
+    primary: bool,
+}
+
+impl MonitorRect {
+    fn contains(&self, (x, y): (i32, i32)) -> bool {
+        (self.left..self.left + self.width).contains(&x) && (self.top..self.top + self.height).contains(&y)
+    }
+}
+
+// 主蒙层所在显示器之外的其它显示器；point 是主蒙层窗口内的一点 (物理像素)
+pub(crate) fn secondary_monitors(monitors: &[MonitorRect], point: (i32, i32)) -> Vec<MonitorRect> {
+    monitors.iter().filter(|m| !m.contains(point)).copied().collect()
+}
+
+// Current 不需要移动窗口；索引无效时回退到主显示器
+pub(crate) fn pick_monitor(monitors: &[MonitorRect], choice: OverlayMonitor) -> Option<MonitorRect> {
+    let primary = || monitors.iter().find(|m| m.primary).or(monitors.first()).copied();
+    match choice {
+        OverlayMonitor::Current => None,
+        OverlayMonitor::Primary => primary(),
+        OverlayMonitor::Index(i) => monitors.get(i).copied().or_else(|| {
+            println!("显示器 {} 不存在，回退到主显示器", i + 1);
+            primary()
+        }),
+    }
+}
+
+// 优先看 XDG_SESSION_TYPE，没有时退回检查 WAYLAND_DISPLAY；Windows 上永远为 false
+pub(crate) fn is_wayland_session(session_type: Option<&str>, wayland_display: bool) -> bool {
+    if !cfg!(target_os = "linux") {
+        return false;
+    }
+    match session_type {
+        Some(kind) if !kind.is_empty() => kind.eq_ignore_ascii_case("wayland"),
+        _ => wayland_display,
+    }
+}
+
+pub(crate) fn window_hidden(ctx: &egui::Context) -> bool {
+    WINDOW_HIDDEN.load(Ordering::SeqCst) || ctx.input(|i| i.viewport().minimized == Some(true))
+}
+
+// 后台线程发送 Webhook，失败只记录日志，不阻塞界面
+pub(crate) fn send_webhook(url: String, body: String) {
+    std::thread::spawn(move || {
+        let result = minreq::post(&url)
+            .with_header("Content-Type", "application/json")
+            .with_body(body)
+            .with_timeout(10)
+            .send();
+        match result {
+            Ok(resp) if (200..300).contains(&resp.status_code) => println!("Webhook 已发送: {}", resp.status_code),
+            Ok(resp) => println!("Webhook 返回异常状态码: {} {}", resp.status_code, resp.reason_phrase),
+            Err(e) => println!("Webhook 发送失败: {}", e),
+        }
+    });
+}
+
+// 按空白拆分命令行，双引号内的空白保留 (用于带空格的路径)；空命令返回 None
+pub(crate) fn split_command(command: &str) -> Option<(String, Vec<String>)> {
+    let mut parts = vec![];
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut has_token = false;
+    for c in command.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                has_token = true;
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if has_token {
+                    parts.push(std::mem::take(&mut current));
+                    has_token = false;
+                }
+            }
+            c => {
+                current.push(c);
+                has_token = true;
+            }
+        }
+    }
+    if has_token {
+        parts.push(current);
+    }
+    let mut parts = parts.into_iter();
+    Some((parts.next()?, parts.collect()))
+}
+
+// 传给外部命令的参数去掉控制字符并限制长度，避免任务名里的换行等干扰脚本
+pub(crate) fn sanitize_trigger_arg(arg: &str) -> String {
+    arg.chars().filter(|c| !c.is_control()).take(200).collect()
+}
+
+// 外部触发命令：直接启动程序而不是 shell，参数不会被当作命令解释；在后台线程等待结束并记录结果
+pub(crate) fn run_trigger_command(command: &str, args: &[&str]) {
+    let Some((program, mut full_args)) = split_command(command) else { return };
+    full_args.extend(args.iter().map(|arg| sanitize_trigger_arg(arg)));
+    println!("运行外部命令: {} {:?}", program, full_args);
+    std::thread::spawn(move || {
+        let mut cmd = std::process::Command::new(&program);
+        cmd.args(&full_args).stdin(std::process::Stdio::null());
+        #[cfg(target_os = "windows")]
+        {
+            use std::os::windows::process::CommandExt;
+            cmd.creation_flags(winapi::um::winbase::CREATE_NO_WINDOW);
+        }
+        match cmd.output() {
+            Ok(output) if output.status.success() => println!("外部命令完成: {}", program),
+            Ok(output) => println!("外部命令 {} 退出状态: {}", program, output.status),
+            Err(e) => println!("无法运行外部命令 {}: {}", program, e),
+        }
+    });
+}
+
+// 开机自启写在 HKEY_CURRENT_USER 下的这个键里，确认框里也显示同样的位置
+pub(crate) const AUTO_START_RUN_KEY: &str = r"SOFTWARE\Microsoft\Windows\CurrentVersion\Run";
+pub(crate) const AUTO_START_VALUE: &str = "RestReminder";
+
+#[cfg(target_os = "windows")]
+pub(crate) fn check_auto_start() -> bool {
+    // 测试中不读写真实的注册表
+    if cfg!(test) {
+        return false;
+    }
+    RegKey::predef(HKEY_CURRENT_USER).open_subkey(AUTO_START_RUN_KEY)
+        .and_then(|k| k.get_value::<String, _>(AUTO_START_VALUE)).is_ok()
+}
+
+#[cfg(target_os = "windows")]
+pub(crate) fn toggle_auto_start(enable: bool) -> std::io::Result<()> {
+    if cfg!(test) {
+        return Ok(());
+    }
+    let key = RegKey::predef(HKEY_CURRENT_USER).create_subkey(AUTO_START_RUN_KEY)?.0;
+    if enable {
+        let path = std::env::current_exe()?;
+        key.set_value(AUTO_START_VALUE, &path.to_string_lossy().as_ref())?;
+    } else if let Err(e) = key.delete_value(AUTO_START_VALUE) {
+        // 本来就没有自启项不算失败
+        if e.kind() != std::io::ErrorKind::NotFound {
+            return Err(e);
+        }
+    }
+    Ok(())
+}
+
+// 启动失败时窗口还没出来，用系统消息框告诉用户原因
+#[cfg(target_os = "windows")]
+pub(crate) fn show_fatal_error(message: &str) {
+    use winapi::um::winuser::{MessageBoxW, MB_ICONERROR, MB_OK};
+    let wide = |text: &str| text.encode_utf16().chain(std::iter::once(0)).collect::<Vec<u16>>();
+    let (text, caption) = (wide(message), wide("番茄钟提醒 启动失败"));
+    unsafe {
+        MessageBoxW(std::ptr::null_mut(), text.as_ptr(), caption.as_ptr(), MB_OK | MB_ICONERROR);
+    }
+}
+
+// 不管"抢占焦点"设置，直接把本程序窗口切到前台
+#[cfg(target_os = "windows")]
+pub(crate) fn force_foreground() {
+    let hwnd = WINDOW_HANDLE.load(Ordering::SeqCst) as HWND;
+    if !hwnd.is_null() {
+        unsafe {
+            SetForegroundWindow(hwnd);
+        }
+    }
+}
+
+// 窗口矩形 (left, top, right, bottom) 完全盖住显示器即视为全屏，包括无边框全屏
+#[cfg(any(target_os = "windows", test))]
+pub(crate) fn covers_monitor(window: (i32, i32, i32, i32), monitor: (i32, i32, i32, i32)) -> bool {
+    window.0 <= monitor.0 && window.1 <= monitor.1 && window.2 >= monitor.2 && window.3 >= monitor.3
+}
+
+// 先问系统 (独占全屏 D3D、演示模式、全屏忙碌)，再比较前台窗口和所在显示器的大小；
+// 桌面、任务栏所在的 shell 窗口和本程序自己的窗口不算
+#[cfg(target_os = "windows")]
+pub(crate) fn fullscreen_app_active() -> bool {
+    use winapi::um::shellapi::{SHQueryUserNotificationState, QUNS_BUSY, QUNS_PRESENTATION_MODE, QUNS_RUNNING_D3D_FULL_SCREEN};
+    use winapi::um::winuser::{GetDesktopWindow, GetForegroundWindow, GetMonitorInfoW, GetShellWindow, GetWindowRect, MonitorFromWindow, MONITORINFO, MONITOR_DEFAULTTONEAREST};
+
+    unsafe {
+        let mut state = 0;
+        if SHQueryUserNotificationState(&mut state) == 0
+            && matches!(state, QUNS_BUSY | QUNS_RUNNING_D3D_FULL_SCREEN | QUNS_PRESENTATION_MODE)
+        {
+            return true;
+        }
+        let hwnd = GetForegroundWindow();
+        let own = WINDOW_HANDLE.load(Ordering::SeqCst) as HWND;
+        if hwnd.is_null() || hwnd == own || hwnd == GetDesktopWindow() || hwnd == GetShellWindow() {
+            return false;
+        }
+        let mut rect = std::mem::zeroed();
+        if GetWindowRect(hwnd, &mut rect) == 0 {
+            return false;
+        }
+        let mut info: MONITORINFO = std::mem::zeroed();
+        info.cbSize = std::mem::size_of::<MONITORINFO>() as u32;
+        if GetMonitorInfoW(MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST), &mut info) == 0 {
+            return false;
+        }
+        let m = info.rcMonitor;
+        covers_monitor((rect.left, rect.top, rect.right, rect.bottom), (m.left, m.top, m.right, m.bottom))
+    }
+}
+
+// 根据系统隐私设置的使用记录判断麦克风/摄像头是否正被占用：LastUsedTimeStop 为 0 表示仍在使用
+#[cfg(target_os = "windows")]
+pub(crate) fn media_device_in_use() -> bool {
+    let consent_store = r"SOFTWARE\Microsoft\Windows\CurrentVersion\CapabilityAccessManager\ConsentStore";
+    let in_use = |key: &RegKey| {
+        key.enum_keys().flatten().any(|name| {
+            let Ok(app) = key.open_subkey(&name) else { return false };
+            let start = app.get_value::<u64, _>("LastUsedTimeStart").unwrap_or(0);
+            let stop = app.get_value::<u64, _>("LastUsedTimeStop").unwrap_or(1);
+            start != 0 && stop == 0
+        })
+    };
+    ["microphone", "webcam"].iter().any(|device| {
+        let Ok(store) = RegKey::predef(HKEY_CURRENT_USER).open_subkey(format!(r"{}\{}", consent_store, device)) else { return false };
+        let in_use_now = in_use(&store) || store.open_subkey("NonPackaged").map(|k| in_use(&k)).unwrap_or(false);
+        if in_use_now {
+            println!("检测到 {} 正在使用", device);
+        }
+        in_use_now
+    })
+}
+
+#[cfg(target_os = "windows")]
+pub(crate) fn list_monitors() -> Vec<MonitorRect> {
+    use winapi::shared::minwindef::{BOOL, LPARAM, TRUE};
+    use winapi::shared::windef::{HDC, HMONITOR, LPRECT};
+    use winapi::um::winuser::{EnumDisplayMonitors, GetMonitorInfoW, MONITORINFO, MONITORINFOF_PRIMARY};
+
+    unsafe extern "system" fn collect(monitor: HMONITOR, _: HDC, _: LPRECT, data: LPARAM) -> BOOL {
+        let monitors = &mut *(data as *mut Vec<MonitorRect>);
+        let mut info: MONITORINFO = std::mem::zeroed();
+        info.cbSize = std::mem::size_of::<MONITORINFO>() as u32;
+        if GetMonitorInfoW(monitor, &mut info) != 0 {
+            monitors.push(MonitorRect {
+                left: info.rcMonitor.left,
+                top: info.rcMonitor.top,
+                width: info.rcMonitor.right - info.rcMonitor.left,
+                height: info.rcMonitor.bottom - info.rcMonitor.top,
+                primary: info.dwFlags & MONITORINFOF_PRIMARY != 0,
+            });
+        }
+        TRUE
+    }
+
+    let mut monitors: Vec<MonitorRect> = vec![];
+    unsafe {
+        EnumDisplayMonitors(std::ptr::null_mut(), std::ptr::null(), Some(collect), &mut monitors as *mut _ as LPARAM);
+    }
+    monitors.sort_by_key(|m| (m.left, m.top));
+    monitors
+}
+
+#[cfg(target_os = "windows")]
+pub(crate) fn move_window_to_monitor(monitor: MonitorRect) {
+    use winapi::um::winuser::{SetWindowPos, SWP_NOACTIVATE, SWP_NOSIZE, SWP_NOZORDER};
+    let hwnd = WINDOW_HANDLE.load(Ordering::SeqCst) as HWND;
+    if hwnd.is_null() {
+        return;
+    }
+    println!("移动窗口到显示器 ({}, {})", monitor.left, monitor.top);
+    unsafe {
+        SetWindowPos(hwnd, std::ptr::null_mut(), monitor.left + 50, monitor.top + 50, 0, 0, SWP_NOSIZE | SWP_NOZORDER | SWP_NOACTIVATE);
+    }
+}
+
+// 按标题找到窗口，铺满显示器的物理像素区域。egui 只能按主窗口的缩放换算逻辑坐标，
+// 各显示器缩放不同时位置和大小会对不上。窗口创建后调用一次；返回是否找到了窗口，没找到下一帧再试
+#[cfg(target_os = "windows")]
+pub(crate) fn fit_window_to_monitor(title: &str, monitor: MonitorRect) -> bool {
+    use winapi::shared::windef::RECT;
+    use winapi::um::winuser::{FindWindowW, GetWindowRect, SetWindowPos, HWND_TOPMOST, SWP_NOACTIVATE};
+    let title: Vec<u16> = title.encode_utf16().chain(std::iter::once(0)).collect();
+    unsafe {
+        let hwnd = FindWindowW(std::ptr::null(), title.as_ptr());
+        if hwnd.is_null() {
+            return false;
+        }
+        let mut rect: RECT = std::mem::zeroed();
+        GetWindowRect(hwnd, &mut rect);
+        let target = (monitor.left, monitor.top, monitor.width, monitor.height);
+        if (rect.left, rect.top, rect.right - rect.left, rect.bottom - rect.top) != target {
+            SetWindowPos(hwnd, HWND_TOPMOST, monitor.left, monitor.top, monitor.width, monitor.height, SWP_NOACTIVATE);
+        }
+    }
+    true
+}
+
+// 光标是否在窗口内的某个区域 (egui 逻辑坐标)；穿透时 egui 收不到鼠标事件，只能直接查系统光标
+#[cfg(target_os = "windows")]
+pub(crate) fn cursor_in_area(area: egui::Rect, pixels_per_point: f32) -> bool {
+    use winapi::shared::windef::POINT;
+    use winapi::um::winuser::{GetCursorPos, ScreenToClient};
+    let hwnd = WINDOW_HANDLE.load(Ordering::SeqCst) as HWND;
+    if hwnd.is_null() {
+        return false;
+    }
+    let mut point = POINT { x: 0, y: 0 };
+    unsafe {
+        if GetCursorPos(&mut point) == 0 || ScreenToClient(hwnd, &mut point) == 0 {
+            return false;
+        }
+    }
+    area.contains(egui::pos2(point.x as f32 / pixels_per_point, point.y as f32 / pixels_per_point))
+}
+
+// WS_EX_TRANSPARENT 只能作用于整个窗口，所以按光标位置逐帧开关；
+// 加 WS_EX_LAYERED 时必须设置一次不透明度，否则窗口会变成不可见
+#[cfg(target_os = "windows")]
+pub(crate) fn set_click_through(enable: bool) {
+    use winapi::um::winuser::{GetWindowLongPtrW, SetLayeredWindowAttributes, SetWindowLongPtrW, GWL_EXSTYLE, LWA_ALPHA, WS_EX_LAYERED, WS_EX_TRANSPARENT};
+    let hwnd = WINDOW_HANDLE.load(Ordering::SeqCst) as HWND;
+    if hwnd.is_null() {
+        return;
+    }
+    unsafe {
+        let ex_style = GetWindowLongPtrW(hwnd, GWL_EXSTYLE) as u32;
+        let flags = WS_EX_LAYERED | WS_EX_TRANSPARENT;
+        let new_style = if enable { ex_style | flags } else { ex_style & !flags };
+        SetWindowLongPtrW(hwnd, GWL_EXSTYLE, new_style as isize);
+        if enable {
+            SetLayeredWindowAttributes(hwnd, 0, 255, LWA_ALPHA);
+        }
+    }
+}
+
+// 前台窗口所属进程的可执行文件名，例如 "Code.exe"；只取进程名，不记录窗口标题
+#[cfg(target_os = "windows")]
+pub(crate) fn foreground_app() -> Option<String> {
+    use winapi::um::handleapi::CloseHandle;
+    use winapi::um::processthreadsapi::OpenProcess;
+    use winapi::um::winbase::QueryFullProcessImageNameW;
+    use winapi::um::winnt::PROCESS_QUERY_LIMITED_INFORMATION;
+    use winapi::um::winuser::{GetForegroundWindow, GetWindowThreadProcessId};
+
+    unsafe {
+        let hwnd = GetForegroundWindow();
+        if hwnd.is_null() {
+            return None;
+        }
+        let mut pid = 0;
+        GetWindowThreadProcessId(hwnd, &mut pid);
+        if pid == 0 {
+            return None;
+        }
+        let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        if process.is_null() {
+            return None;
+        }
+        let mut buf = [0u16; 260];
+        let mut len = buf.len() as u32;
+        let ok = QueryFullProcessImageNameW(process, 0, buf.as_mut_ptr(), &mut len);
+        CloseHandle(process);
+        if ok == 0 {
+            return None;
+        }
+        let path = String::from_utf16_lossy(&buf[..len as usize]);
+        std::path::Path::new(&path).file_name().map(|name| name.to_string_lossy().into_owned())
+    }
+}
+
+// 用一个隐藏的消息窗口接收 WM_WTSSESSION_CHANGE，把锁屏/解锁写入 SESSION_LOCKED
+#[cfg(target_os = "windows")]
+pub(crate) fn watch_session_lock(ctx: egui::Context) {
+    use std::sync::OnceLock;
+    use winapi::shared::minwindef::{LPARAM, LRESULT, UINT, WPARAM};
+    use winapi::um::libloaderapi::GetModuleHandleW;
+    use winapi::um::winuser::{
+        CreateWindowExW, DefWindowProcW, DispatchMessageW, GetMessageW, RegisterClassW, TranslateMessage,
+        HWND_MESSAGE, MSG, WM_WTSSESSION_CHANGE, WNDCLASSW, WTS_SESSION_LOCK, WTS_SESSION_UNLOCK,
+    };
+
+    // winapi 0.3 没有导出 wtsapi32 的这个函数
+    #[link(name = "wtsapi32")]
+    extern "system" {
+        fn WTSRegisterSessionNotification(hwnd: HWND, flags: u32) -> i32;
+    }
+    const NOTIFY_FOR_THIS_SESSION: u32 = 0;
+
+    static REPAINT_CTX: OnceLock<egui::Context> = OnceLock::new();
+    let _ = REPAINT_CTX.set(ctx);
+
+    unsafe extern "system" fn wnd_proc(hwnd: HWND, msg: UINT, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+        if msg == WM_WTSSESSION_CHANGE {
+            match wparam {
+                WTS_SESSION_LOCK => SESSION_LOCKED.store(true, Ordering::SeqCst),
+                WTS_SESSION_UNLOCK => SESSION_LOCKED.store(false, Ordering::SeqCst),
+                _ => {}
+            }
+            if let Some(ctx) = REPAINT_CTX.get() {
+                ctx.request_repaint();
+            }
+            return 0;
+        }
+        DefWindowProcW(hwnd, msg, wparam, lparam)
+    }
+
+    std::thread::spawn(|| unsafe {
+        let class_name: Vec<u16> = "RestReminderSessionWatcher\0".encode_utf16().collect();
+        let instance = GetModuleHandleW(std::ptr::null());
+        let class = WNDCLASSW {
+            lpfnWndProc: Some(wnd_proc),
+            hInstance: instance,
+            lpszClassName: class_name.as_ptr(),
+            ..std::mem::zeroed()
+        };
+        RegisterClassW(&class);
+        let hwnd = CreateWindowExW(
+            0, class_name.as_ptr(), std::ptr::null(), 0, 0, 0, 0, 0,
+            HWND_MESSAGE, std::ptr::null_mut(), instance, std::ptr::null_mut(),
+        );
+        if hwnd.is_null() || WTSRegisterSessionNotification(hwnd, NOTIFY_FOR_THIS_SESSION) == 0 {
+            println!("无法注册锁屏通知，锁屏时不会自动暂停");
+            return;
+        }
+        println!("锁屏监听已启动");
+        let mut msg: MSG = std::mem::zeroed();
+        while GetMessageW(&mut msg, std::ptr::null_mut(), 0, 0) > 0 {
+            TranslateMessage(&msg);
+            DispatchMessageW(&msg);
+        }
+    });
+}
+
+#[cfg(not(target_os = "windows"))] pub(crate) fn check_auto_start() -> bool { false }
+#[cfg(not(target_os = "windows"))] pub(crate) fn toggle_auto_start(_: bool) -> std::io::Result<()> { Ok(()) }
+#[cfg(not(target_os = "windows"))] pub(crate) fn media_device_in_use() -> bool { false }
+#[cfg(not(target_os = "windows"))] pub(crate) fn watch_session_lock(_: egui::Context) {}
+#[cfg(not(target_os = "windows"))] pub(crate) fn foreground_app() -> Option<String> { None }
+#[cfg(not(target_os = "windows"))] pub(crate) fn fullscreen_app_active() -> bool { false }
+#[cfg(not(target_os = "windows"))] pub(crate) fn force_foreground() {}
+// 其它平台从终端启动，错误已经打印在日志里
+#[cfg(not(target_os = "windows"))] pub(crate) fn show_fatal_error(_: &str) {}
+// 其它平台拿不到显示器列表，蒙层留在窗口所在的显示器
+#[cfg(not(target_os = "windows"))] pub(crate) fn list_monitors() -> Vec<MonitorRect> { vec![] }
+#[cfg(not(target_os = "windows"))] pub(crate) fn move_window_to_monitor(_: MonitorRect) {}
+#[cfg(not(target_os = "windows"))] pub(crate) fn fit_window_to_monitor(_: &str, _: MonitorRect) -> bool { true }
+#[cfg(target_os = "linux")]
+pub(crate) fn play_sound(id: &str, _: Option<std::path::PathBuf>, _: Option<String>) {
+    println!("Linux 版本暂不支持播放声音: {}", sound_label(id));
+}
+#[cfg(target_os = "linux")]
+pub(crate) fn list_audio_devices() -> Result<Vec<String>, String> {
+    Err("Linux 版本暂不支持播放声音".to_string())
+}
+// Linux 上没有启用 rfd (依赖 Wayland/GTK 开发包)，导入导出固定使用主目录下的文件
+#[cfg(target_os = "linux")]
+pub(crate) fn pick_settings_file(_save: bool) -> Option<std::path::PathBuf> {
+    let path = dirs::home_dir()?.join(SETTINGS_EXPORT_NAME);
+    println!("Linux 版本暂不支持文件对话框，使用 {}", path.display());
+    Some(path)
+}
+// 其它平台不支持鼠标穿透，设置项也只在 Windows 上显示
+#[cfg(not(target_os = "windows"))] pub(crate) fn cursor_in_area(_: egui::Rect, _: f32) -> bool { false }
+#[cfg(not(target_os = "windows"))] pub(crate) fn set_click_through(_: bool) {}
+
+// 系统里的音频输出设备名；拿不到设备列表时返回原因
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn list_audio_devices() -> Result<Vec<String>, String> {
+    use rodio::cpal::traits::{DeviceTrait, HostTrait};
+    let devices = rodio::cpal::default_host().output_devices().map_err(|e| e.to_string())?;
+    Ok(devices.filter_map(|device| device.name().ok()).collect())
+}
+
+// 指定的设备不存在或打不开时退回系统默认设备
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn open_audio_output(name: Option<&str>) -> Option<(rodio::OutputStream, rodio::OutputStreamHandle)> {
+    use rodio::cpal::traits::{DeviceTrait, HostTrait};
+    if let Some(name) = name {
+        let device = rodio::cpal::default_host()
+            .output_devices()
+            .ok()
+            .and_then(|mut devices| devices.find(|device| device.name().is_ok_and(|n| n == name)));
+        match device.map(|device| rodio::OutputStream::try_from_device(&device)) {
+            Some(Ok(output)) => return Some(output),
+            Some(Err(e)) => println!("无法打开音频设备 {}: {}，改用系统默认", name, e),
+            None => println!("找不到音频设备 {}，改用系统默认", name),
+        }
+    }
+    rodio::OutputStream::try_default().ok()
+}
+
+// 对话框会阻塞界面直到用户选完，取消时返回 None
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn pick_settings_file(save: bool) -> Option<std::path::PathBuf> {
+    let dialog = rfd::FileDialog::new()
+        .add_filter("JSON", &["json"])
+        .add_filter("TOML", &["toml"])
+        .set_file_name(SETTINGS_EXPORT_NAME);
+    if save { dialog.save_file() } else { dialog.pick_file() }
+}
+
+// 在后台线程里播放，播完线程结束；文件缺失或格式不支持只记录日志
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn play_sound(id: &str, sounds_dir: Option<std::path::PathBuf>, device: Option<String>) {
+    use rodio::Source;
+    let id = id.to_string();
+    std::thread::spawn(move || {
+        let Some((_stream, handle)) = open_audio_output(device.as_deref()) else {
+            return println!("没有可用的音频输出设备");
+        };
+        let sink = match rodio::Sink::try_new(&handle) {
+            Ok(sink) => sink,
+            Err(e) => return println!("无法创建音频输出: {}", e),
+        };
+        let tone = |freq: f32, ms: u64| rodio::source::SineWave::new(freq).take_duration(Duration::from_millis(ms)).amplify(0.2);
+        match id.as_str() {
+            "builtin:ding" => sink.append(tone(880.0, 250)),
+            "builtin:chime" => {
+                sink.append(tone(660.0, 180));
+                sink.append(tone(990.0, 260));
+            }
+            name => {
+                let Some(path) = sounds_dir.map(|dir| dir.join(name)) else { return };
+                let file = match std::fs::File::open(&path) {
+                    Ok(file) => file,
+                    Err(e) => return println!("无法打开声音文件 {}: {}", path.display(), e),
+                };
+                match rodio::Decoder::new(std::io::BufReader::new(file)) {
+                    Ok(source) => sink.append(source),
+                    Err(e) => return println!("无法解码声音文件 {}: {}", path.display(), e),
+                }
+            }
+        }
+        sink.sleep_until_end();
+    });
+}
+
+// 直接显示窗口的函数 (在托盘线程中调用)
+#[cfg(target_os = "windows")]
+pub(crate) fn show_window_directly() {
+    let hwnd = WINDOW_HANDLE.load(Ordering::SeqCst) as HWND;
+    let steal_focus = STEAL_FOCUS_ON_SHOW.load(Ordering::SeqCst);
+    if !hwnd.is_null() {
+        println!("直接调用 Windows API 显示窗口: {:?}", hwnd);
+        WINDOW_HIDDEN.store(false, Ordering::SeqCst);
+        unsafe {
+            if !steal_focus {
+                // 只显示不激活
+                ShowWindow(hwnd, SW_SHOWNOACTIVATE);
+                return;
+            }
+
+            // 先显示窗口
+            ShowWindow(hwnd, SW_SHOW);
+
+            // 强制获取焦点和前台
+            SetForegroundWindow(hwnd);
+
+            // 额外：确保窗口不是全屏状态
+            use winapi::um::winuser::{GetWindowLongPtrW, SetWindowLongPtrW, GWL_STYLE, GWL_EXSTYLE, WS_OVERLAPPEDWINDOW, WS_EX_APPWINDOW};
+
+            // 获取当前样式
+            let mut style = GetWindowLongPtrW(hwnd, GWL_STYLE) as u32;
+            let mut ex_style = GetWindowLongPtrW(hwnd, GWL_EXSTYLE) as u32;
+
+            // 确保有标题栏和边框
+            style |= WS_OVERLAPPEDWINDOW;
+            ex_style |= WS_EX_APPWINDOW;
+
+            SetWindowLongPtrW(hwnd, GWL_STYLE, style as isize);
+            SetWindowLongPtrW(hwnd, GWL_EXSTYLE, ex_style as isize);
+
+            // 最后再次确保窗口正常显示
+            ShowWindow(hwnd, SW_RESTORE);
+            SetForegroundWindow(hwnd);
+        }
+    } else {
+        println!("窗口句柄为空，无法直接显示");
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub(crate) fn show_window_directly() {
+    // 交给主循环显示；托盘线程随后会请求重绘把主循环唤醒
+    println!("非 Windows 系统，不使用直接窗口调用");
+    TRAY_SHOW_REQUEST.store(true, Ordering::SeqCst);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overlay_monitor_selection() {
+        let monitors = [
+            MonitorRect { left: -1920, top: 0, width: 1920, height: 1080, primary: false },
+            MonitorRect { left: 0, top: 0, width: 1920, height: 1080, primary: true },
+            MonitorRect { left: 1920, top: 0, width: 2560, height: 1440, primary: false },
+        ];
+        assert_eq!(pick_monitor(&monitors, OverlayMonitor::Current), None);
+        assert_eq!(pick_monitor(&monitors, OverlayMonitor::Primary), Some(monitors[1]));
+        assert_eq!(pick_monitor(&monitors, OverlayMonitor::Index(2)), Some(monitors[2]));
+        assert_eq!(pick_monitor(&monitors, OverlayMonitor::Index(5)), Some(monitors[1]), "无效索引回退到主显示器");
+        assert_eq!(pick_monitor(&[], OverlayMonitor::Primary), None);
+
+        assert_eq!(secondary_monitors(&monitors, (960, 540)), vec![monitors[0], monitors[2]]);
+        assert_eq!(secondary_monitors(&monitors, (-1, 0)), vec![monitors[1], monitors[2]], "左边显示器的最右一列");
+        assert_eq!(secondary_monitors(&monitors[1..2], (960, 540)), vec![]);
+    }
+
+    #[test]
+    fn wayland_session_detection() {
+        let linux = cfg!(target_os = "linux");
+        assert_eq!(is_wayland_session(Some("wayland"), false), linux);
+        assert!(!is_wayland_session(Some("x11"), true), "XDG_SESSION_TYPE 优先");
+        assert_eq!(is_wayland_session(None, true), linux);
+        assert_eq!(is_wayland_session(Some(""), true), linux);
+        assert!(!is_wayland_session(None, false));
+    }
+
+    #[test]
+    fn scan_sounds_filters_supported_files() {
+        let dir = std::env::temp_dir().join(format!("remindrest-sounds-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("sub.wav")).unwrap();
+        for name in ["b.MP3", "a.wav", "notes.txt", "noext"] {
+            std::fs::write(dir.join(name), b"").unwrap();
+        }
+        assert_eq!(scan_sounds(&dir), vec!["a.wav".to_string(), "b.MP3".to_string()]);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(scan_sounds(&dir).is_empty(), "文件夹不存在时为空");
+        assert_eq!(sound_label("builtin:ding"), "叮 (内置)");
+        assert_eq!(sound_label("rain.mp3"), "rain.mp3");
+    }
+
+    #[test]
+    fn trigger_command_parsing() {
+        assert_eq!(split_command("  "), None);
+        let (program, args) = split_command(r#""C:\Program Files\hue.exe" --color red"#).unwrap();
+        assert_eq!(program, r"C:\Program Files\hue.exe");
+        assert_eq!(args, vec!["--color".to_string(), "red".to_string()]);
+        assert_eq!(split_command(r#"echo """#).unwrap().1, vec![String::new()], "空引号是一个空参数");
+        assert_eq!(sanitize_trigger_arg("写\n文档\t; rm"), "写文档; rm");
+        assert_eq!(sanitize_trigger_arg(&"长".repeat(300)).chars().count(), 200);
+    }
+}
@@ -0,0 +1,508 @@
+//! 专注统计 (stats.json)、每段专注/休息的流水 (sessions.db) 和专注记录 (notes.jsonl)
+
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::Ordering;
+
+use crate::*;
+
+pub(crate) const MILESTONES: [u64; 3] = [100, 500, 1000];
+
+// 统计文件的写入串行进行，锁里记着已写入的最新版本号；
+// 后台自动保存的快照如果晚于主线程的保存才轮到，就直接丢弃，不用旧数据覆盖新数据
+pub(crate) static STATS_WRITTEN: std::sync::Mutex<u64> = std::sync::Mutex::new(0);
+pub(crate) static STATS_GENERATION: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+
+// 按本地日期统计的专注记录，保存在配置文件旁的 stats.json
+#[derive(Serialize, Deserialize, Default, Clone)]
+#[serde(default)]
+pub(crate) struct Stats {
+    pub(crate) days: std::collections::BTreeMap<chrono::NaiveDate, DayStats>,
+    pub(crate) total_completed: u64,           // 累计完成的番茄数
+    pub(crate) celebrated_milestones: Vec<u64>, // 已经庆祝过的里程碑，不重复庆祝
+    pub(crate) last_plan: u32,                  // 最近一次设的每日计划，新的一天没设计划时沿用
+}
+
+#[derive(Serialize, Deserialize, Default, Clone)]
+#[serde(default)]
+pub(crate) struct DayStats {
+    pub(crate) completed_work: u32,
+    pub(crate) focus_secs: u64,
+    pub(crate) skipped_rests: u32,
+    pub(crate) snoozes: u32, // 推迟休息的次数
+    // 专注时前台程序的进程名 -> 秒数，仅在开启 track_apps 时记录，只保存在本机
+    pub(crate) apps: std::collections::BTreeMap<String, u64>,
+    pub(crate) reminders: std::collections::BTreeMap<ReminderType, u32>, // 各类健康提醒完成的次数
+    pub(crate) sessions: Vec<SessionBlock>, // 当天每段专注/休息的起止时间，用于时间轴
+    pub(crate) water_cups: u32,             // 完成喝水提醒或手动 +1 的杯数
+    pub(crate) rest_secs: u64,              // 实际休息的秒数 (跳过的休息只算已休息的部分)
+    pub(crate) planned_sessions: u32,       // 当天计划完成的番茄数，0 表示当天还没设
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub(crate) enum SessionKind {
+    Work,
+    Rest,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub(crate) struct SessionBlock {
+    pub(crate) kind: SessionKind,
+    pub(crate) start: chrono::NaiveTime,
+    pub(crate) end: chrono::NaiveTime,
+    pub(crate) task: Option<String>,
+    #[serde(default)]
+    pub(crate) skipped: bool, // 跳过的休息或放弃的专注，主界面的记录点画成空心
+    #[serde(default)]
+    pub(crate) in_progress: bool, // 自动保存时还没结束的一段；程序没能正常退出时留在文件里，显示为中断
+}
+
+// 每日专注分 = 完成数 × SCORE_COMPLETED − 跳过休息数 × SCORE_SKIPPED − 推迟数 × SCORE_SNOOZED，最低为 0。
+// 跳过休息扣分比推迟多，因为推迟至少还会休息
+pub(crate) const SCORE_COMPLETED: i64 = 10;
+pub(crate) const SCORE_SKIPPED: i64 = 5;
+pub(crate) const SCORE_SNOOZED: i64 = 2;
+
+impl DayStats {
+    pub(crate) fn focus_score(&self) -> u32 {
+        let score = self.completed_work as i64 * SCORE_COMPLETED
+            - self.skipped_rests as i64 * SCORE_SKIPPED
+            - self.snoozes as i64 * SCORE_SNOOZED;
+        score.max(0) as u32
+    }
+}
+
+// notes.jsonl 中的一行：一次专注结束时写下的记录
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub(crate) struct SessionNote {
+    pub(crate) timestamp: String, // RFC 3339，本地时区
+    pub(crate) duration_secs: u64,
+    pub(crate) task: Option<String>,
+    pub(crate) note: String,
+}
+
+// sessions.db 中的一条：一段专注或休息，包括跳过的休息和中途放弃的专注
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub(crate) struct SessionRecord {
+    pub(crate) kind: SessionKind,
+    pub(crate) start: chrono::NaiveDateTime,
+    pub(crate) end: chrono::NaiveDateTime,
+    pub(crate) duration_secs: u64, // 实际计时的秒数，不含暂停
+    pub(crate) finished: bool,     // false 表示跳过的休息或中途放弃的专注
+}
+
+// 一段时间内流水记录的汇总
+#[derive(Default, Clone, Copy, PartialEq, Debug)]
+pub(crate) struct SessionTotals {
+    pub(crate) finished_work: u32,
+    pub(crate) abandoned_work: u32,
+    pub(crate) finished_rest: u32,
+    pub(crate) skipped_rest: u32,
+}
+
+// 流水记录存在配置文件旁的 sled 数据库里；测试、仅内存模式或打开失败时为 None，记录直接丢弃
+#[derive(Default)]
+pub(crate) struct SessionLog {
+    db: Option<sled::Db>,
+}
+
+impl SessionLog {
+    fn path() -> Option<std::path::PathBuf> {
+        AppConfig::config_path().and_then(|p| Some(p.parent()?.join("sessions.db")))
+    }
+
+    pub(crate) fn open(warnings: &mut Vec<String>) -> Self {
+        let Some(path) = Self::path() else { return Self::default() };
+        match sled::open(&path) {
+            Ok(db) => Self { db: Some(db) },
+            Err(e) => {
+                println!("打开专注流水失败: {}", e);
+                warnings.push(format!("专注流水 {} 无法打开，本次运行不记录每段专注", path.display()));
+                Self::default()
+            }
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn temporary() -> Self {
+        Self { db: Some(sled::Config::new().temporary(true).open().unwrap()) }
+    }
+
+    pub(crate) fn is_open(&self) -> bool {
+        self.db.is_some()
+    }
+
+    // 键是开始时间加递增编号: 字典序就是时间先后，同一秒开始的两段也不会互相覆盖。返回键，撤销时用来删除
+    pub(crate) fn record(&self, record: &SessionRecord) -> Option<Vec<u8>> {
+        let db = self.db.as_ref()?;
+        let result = db.generate_id().and_then(|id| {
+            let key = format!("{}#{:020}", record.start.format("%Y-%m-%dT%H:%M:%S%.6f"), id).into_bytes();
+            let value = serde_json::to_vec(record).map_err(|e| sled::Error::Unsupported(e.to_string()))?;
+            db.insert(&key, value)?;
+            db.flush()?;
+            Ok(key)
+        });
+        result.map_err(|e| report_save_error(format!("保存专注流水失败: {}", e))).ok()
+    }
+
+    pub(crate) fn remove(&self, key: &[u8]) {
+        let Some(db) = &self.db else { return };
+        if let Err(e) = db.remove(key).and_then(|_| db.flush()) {
+            report_save_error(format!("删除专注流水失败: {}", e));
+        }
+    }
+
+    // from 到 to 两天 (含) 之间开始的记录，按开始时间排列；解析失败的跳过
+    pub(crate) fn between(&self, from: chrono::NaiveDate, to: chrono::NaiveDate) -> Vec<SessionRecord> {
+        let Some(db) = &self.db else { return vec![] };
+        let Some(end) = to.succ_opt() else { return vec![] };
+        let range = from.format("%Y-%m-%d").to_string().into_bytes()..end.format("%Y-%m-%d").to_string().into_bytes();
+        db.range(range).values().filter_map(|value| serde_json::from_slice(&value.ok()?).ok()).collect()
+    }
+
+    pub(crate) fn totals(&self, from: chrono::NaiveDate, to: chrono::NaiveDate) -> SessionTotals {
+        self.between(from, to).iter().fold(SessionTotals::default(), |mut totals, record| {
+            match (record.kind, record.finished) {
+                (SessionKind::Work, true) => totals.finished_work += 1,
+                (SessionKind::Work, false) => totals.abandoned_work += 1,
+                (SessionKind::Rest, true) => totals.finished_rest += 1,
+                (SessionKind::Rest, false) => totals.skipped_rest += 1,
+            }
+            totals
+        })
+    }
+}
+
+impl SessionNote {
+    pub(crate) const RECENT: usize = 5;
+
+    fn path() -> Option<std::path::PathBuf> {
+        AppConfig::config_path().and_then(|p| Some(p.parent()?.join("notes.jsonl")))
+    }
+
+    // 只追加，不改写已有内容；上次写到一半被中断时文件末尾没有换行，先补一个换行，避免新记录拼到残缺行上
+    pub(crate) fn append(&self) {
+        use std::io::{Read, Seek, Write};
+        let Some(path) = Self::path() else { return };
+        if let Some(dir) = path.parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        let line = match serde_json::to_string(self) {
+            Ok(line) => line,
+            Err(e) => return println!("序列化记录失败: {}", e),
+        };
+        let result = std::fs::OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| {
+                let mut last = [b'\n'];
+                if file.metadata()?.len() > 0 {
+                    file.seek(std::io::SeekFrom::End(-1))?;
+                    file.read_exact(&mut last)?;
+                }
+                let separator = if last[0] == b'\n' { "" } else { "\n" };
+                writeln!(file, "{}{}", separator, line)
+            });
+        if let Err(e) = result {
+            report_save_error(format!("保存记录失败: {}", e));
+        }
+    }
+
+    // 读取最近的几条，解析失败的行直接跳过
+    pub(crate) fn load_recent() -> Vec<Self> {
+        let Some(text) = Self::path().and_then(|path| std::fs::read_to_string(path).ok()) else { return vec![] };
+        Self::parse_recent(&text)
+    }
+
+    pub(crate) fn parse_recent(text: &str) -> Vec<Self> {
+        let notes: Vec<Self> = text.lines().filter_map(|line| serde_json::from_str(line).ok()).collect();
+        notes[notes.len().saturating_sub(Self::RECENT)..].to_vec()
+    }
+}
+
+impl Stats {
+    fn path() -> Option<std::path::PathBuf> {
+        AppConfig::config_path().and_then(|p| Some(p.parent()?.join("stats.json")))
+    }
+
+    pub(crate) fn load(warnings: &mut Vec<String>) -> Self {
+        let Some(path) = Self::path() else { return Self::default() };
+        let mut stats: Self = match std::fs::read_to_string(&path) {
+            Ok(text) => serde_json::from_str(&text).unwrap_or_else(|e| {
+                println!("统计文件解析失败，重新开始统计: {}", e);
+                warnings.push(format!("统计文件 {} 无法读取，已重新开始统计", path.display()));
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        };
+        if stats.total_completed == 0 {
+            // 旧的统计文件没有累计数，用每日记录补上
+            stats.total_completed = stats.days.values().map(|day| day.completed_work as u64).sum();
+        }
+        stats
+    }
+
+    pub(crate) fn save(&self) {
+        if let Some((path, text, generation)) = self.prepare_save() {
+            Self::write(&path, &text, generation);
+        }
+    }
+
+    // 序列化在调用线程做 (很快)，写盘和 sync 放到后台线程，避免界面卡顿
+    pub(crate) fn save_in_background(&self) {
+        if let Some((path, text, generation)) = self.prepare_save() {
+            std::thread::spawn(move || Self::write(&path, &text, generation));
+        }
+    }
+
+    fn prepare_save(&self) -> Option<(std::path::PathBuf, String, u64)> {
+        let path = Self::path()?;
+        let generation = STATS_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+        match serde_json::to_string_pretty(self) {
+            Ok(text) => Some((path, text, generation)),
+            Err(e) => {
+                println!("序列化统计失败: {}", e);
+                None
+            }
+        }
+    }
+
+    fn write(path: &std::path::Path, text: &str, generation: u64) {
+        let mut written = STATS_WRITTEN.lock().unwrap_or_else(|e| e.into_inner());
+        if generation < *written {
+            return;
+        }
+        if let Some(dir) = path.parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        match write_atomic(path, text) {
+            Ok(()) => *written = generation,
+            Err(e) => report_save_error(format!("保存统计失败: {}", e)),
+        }
+    }
+
+    pub(crate) fn day(&self, date: chrono::NaiveDate) -> Option<&DayStats> {
+        self.days.get(&date)
+    }
+
+    pub(crate) fn completed_on(&self, date: chrono::NaiveDate) -> u32 {
+        self.day(date).map_or(0, |day| day.completed_work)
+    }
+
+    // 本周 (周一到 date 当天) 完成的番茄数和专注秒数
+    pub(crate) fn week_totals(&self, date: chrono::NaiveDate) -> (u32, u64) {
+        let monday = week_start(date);
+        self.days.range(monday..=date).fold((0, 0), |(completed, secs), (_, day)| (completed + day.completed_work, secs + day.focus_secs))
+    }
+
+    pub(crate) fn record_app_time(&mut self, date: chrono::NaiveDate, app: String, secs: u64) {
+        *self.days.entry(date).or_default().apps.entry(app).or_default() += secs;
+    }
+
+    // 按时长倒序取前 n 个程序
+    pub(crate) fn top_apps(&self, date: chrono::NaiveDate, n: usize) -> Vec<(&str, u64)> {
+        let Some(day) = self.day(date) else { return vec![] };
+        let mut apps: Vec<_> = day.apps.iter().map(|(name, secs)| (name.as_str(), *secs)).collect();
+        apps.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        apps.truncate(n);
+        apps
+    }
+
+    pub(crate) fn record_work(&mut self, date: chrono::NaiveDate, minutes: u64) {
+        let day = self.days.entry(date).or_default();
+        day.completed_work += 1;
+        day.focus_secs += minutes.saturating_mul(60);
+        self.total_completed += 1;
+    }
+
+    // 记在开始那天；跨过午夜的部分截断到当天结束
+    pub(crate) fn record_session(&mut self, kind: SessionKind, start: chrono::NaiveDateTime, end: chrono::NaiveDateTime, task: Option<String>, skipped: bool) {
+        let end = if end.date() == start.date() { end.time() } else { chrono::NaiveTime::from_hms_opt(23, 59, 59).unwrap() };
+        let block = SessionBlock { kind, start: start.time(), end: end.max(start.time()), task, skipped, in_progress: false };
+        self.days.entry(start.date()).or_default().sessions.push(block);
+    }
+
+    // 最近 n 段记录 (可以跨天)，按时间先后排列
+    pub(crate) fn recent_sessions(&self, n: usize) -> Vec<&SessionBlock> {
+        let mut recent: Vec<_> = self.days.values().rev().flat_map(|day| day.sessions.iter().rev()).take(n).collect();
+        recent.reverse();
+        recent
+    }
+
+    pub(crate) fn record_reminder(&mut self, date: chrono::NaiveDate, kind: ReminderType) {
+        *self.days.entry(date).or_default().reminders.entry(kind).or_default() += 1;
+    }
+
+    // 当天设过计划就用当天的，否则沿用上一次的计划；0 表示没有计划
+    pub(crate) fn planned_on(&self, date: chrono::NaiveDate) -> u32 {
+        self.day(date).map(|day| day.planned_sessions).filter(|&plan| plan > 0).unwrap_or(self.last_plan)
+    }
+
+    pub(crate) fn set_plan(&mut self, date: chrono::NaiveDate, sessions: u32) {
+        self.days.entry(date).or_default().planned_sessions = sessions;
+        self.last_plan = sessions;
+    }
+
+    pub(crate) fn record_rest(&mut self, date: chrono::NaiveDate, secs: u64) {
+        self.days.entry(date).or_default().rest_secs += secs;
+    }
+
+    pub(crate) fn record_water(&mut self, date: chrono::NaiveDate) {
+        self.days.entry(date).or_default().water_cups += 1;
+    }
+
+    pub(crate) fn record_snooze(&mut self, date: chrono::NaiveDate) {
+        self.days.entry(date).or_default().snoozes += 1;
+    }
+
+    pub(crate) fn record_skipped_rest(&mut self, date: chrono::NaiveDate) {
+        self.days.entry(date).or_default().skipped_rests += 1;
+    }
+
+    // 刚达到、还没庆祝过的最高里程碑；一次跨过多个时只庆祝最高的那个
+    pub(crate) fn take_new_milestone(&mut self) -> Option<u64> {
+        let reached: Vec<u64> = MILESTONES.iter().copied()
+            .filter(|m| *m <= self.total_completed && !self.celebrated_milestones.contains(m))
+            .collect();
+        self.celebrated_milestones.extend(&reached);
+        reached.last().copied()
+    }
+}
+
+pub(crate) fn today() -> chrono::NaiveDate {
+    chrono::Local::now().date_naive()
+}
+
+// date 所在那一周的周一
+pub(crate) fn week_start(date: chrono::NaiveDate) -> chrono::NaiveDate {
+    use chrono::Datelike;
+    date - chrono::Days::new(u64::from(date.weekday().num_days_from_monday()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    #[test]
+    fn app_usage_top_list() {
+        let mut stats = Stats::default();
+        let date = today();
+        assert!(stats.top_apps(date, 5).is_empty());
+        stats.record_app_time(date, "Code.exe".to_string(), 300);
+        stats.record_app_time(date, "chrome.exe".to_string(), 120);
+        stats.record_app_time(date, "Code.exe".to_string(), 60);
+        assert_eq!(stats.top_apps(date, 5), vec![("Code.exe", 360), ("chrome.exe", 120)]);
+        assert_eq!(stats.top_apps(date, 1).len(), 1);
+    }
+
+    #[test]
+    fn week_totals_start_on_monday() {
+        let mut stats = Stats::default();
+        // 2026-03-01 是周日，03-02 是周一
+        let sunday = chrono::NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+        let monday = sunday.succ_opt().unwrap();
+        let wednesday = monday + chrono::Days::new(2);
+        stats.record_work(sunday, 25);
+        stats.record_work(monday, 25);
+        stats.record_work(wednesday, 45);
+        stats.record_work(wednesday + chrono::Days::new(1), 25);
+        assert_eq!(stats.week_totals(wednesday), (2, 70 * 60));
+        assert_eq!(stats.week_totals(sunday), (1, 25 * 60), "周日属于上一周");
+    }
+
+    #[test]
+    fn session_log_keeps_every_session_and_abandoned_work() {
+        let log = SessionLog::temporary();
+        let date = chrono::NaiveDate::from_ymd_opt(2026, 3, 4).unwrap();
+        let at = |d: chrono::NaiveDate, h, m| d.and_hms_opt(h, m, 0).unwrap();
+        let record = |start, end, kind, finished| SessionRecord { kind, start, end, duration_secs: 60, finished };
+        log.record(&record(at(date, 9, 0), at(date, 9, 25), SessionKind::Work, true));
+        // 同一秒开始的两段都保留
+        log.record(&record(at(date, 9, 25), at(date, 9, 30), SessionKind::Rest, false));
+        log.record(&record(at(date, 9, 25), at(date, 9, 40), SessionKind::Work, false));
+        log.record(&record(at(week_start(date), 9, 0), at(week_start(date), 9, 25), SessionKind::Work, true));
+        log.record(&record(at(date.succ_opt().unwrap(), 9, 0), at(date.succ_opt().unwrap(), 9, 25), SessionKind::Work, true));
+        assert_eq!(log.between(date, date).len(), 3);
+        let day = log.totals(date, date);
+        assert_eq!((day.finished_work, day.abandoned_work, day.finished_rest, day.skipped_rest), (1, 1, 0, 1));
+        assert_eq!(log.totals(week_start(date), date).finished_work, 2, "周一的也算进本周");
+        assert!(SessionLog::default().between(date, date).is_empty());
+
+        let (_tx, rx) = mpsc::channel();
+        let mut app = RestReminderApp::from_parts(AppConfig::default(), rx, None);
+        app.session_log = SessionLog::temporary();
+        app.start_work();
+        app.advance_clock(Duration::from_secs(10 * 60));
+        app.start_work();
+        app.apply_shortcut(ShortcutAction::Reset);
+        app.start_work();
+        app.advance_clock(Duration::from_secs(25 * 60));
+        app.tick();
+        app.config.skip_undo_secs = 10;
+        app.skip_rest();
+        let records = app.session_log.between(today(), today());
+        let summary: Vec<_> = records.iter().map(|r| (r.kind, r.finished)).collect();
+        assert_eq!(summary, vec![(SessionKind::Work, false), (SessionKind::Work, false), (SessionKind::Work, true), (SessionKind::Rest, false)]);
+        assert_eq!(records[0].duration_secs, 10 * 60, "放弃的专注记实际专注的时长");
+        assert_eq!(records[2].duration_secs, 25 * 60);
+        app.undo_skip();
+        assert_eq!(app.session_log.between(today(), today()).len(), 3, "撤销跳过后流水里也删掉");
+    }
+
+    #[test]
+    fn sessions_are_recorded_on_their_start_day() {
+        let mut stats = Stats::default();
+        let date = chrono::NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+        let at = |d: chrono::NaiveDate, h, m| d.and_hms_opt(h, m, 0).unwrap();
+        stats.record_session(SessionKind::Work, at(date, 9, 0), at(date, 9, 25), Some("周报".to_string()), false);
+        stats.record_session(SessionKind::Rest, at(date, 23, 55), at(date.succ_opt().unwrap(), 0, 5), None, false);
+        let sessions = &stats.day(date).unwrap().sessions;
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].end, chrono::NaiveTime::from_hms_opt(9, 25, 0).unwrap());
+        assert_eq!(sessions[1].end, chrono::NaiveTime::from_hms_opt(23, 59, 59).unwrap(), "跨午夜截断");
+        assert!(stats.day(date.succ_opt().unwrap()).is_none());
+
+        let (_tx, rx) = mpsc::channel();
+        let mut app = RestReminderApp::from_parts(AppConfig::default(), rx, None);
+        app.start_work();
+        app.time_remaining = Duration::ZERO;
+        app.tick();
+        app.skip_rest();
+        let kinds: Vec<_> = app.stats.day(today()).unwrap().sessions.iter().map(|b| b.kind).collect();
+        assert_eq!(kinds, vec![SessionKind::Work, SessionKind::Rest]);
+    }
+
+    #[test]
+    fn recent_sessions_span_days_and_mark_skips() {
+        let mut stats = Stats::default();
+        let date = chrono::NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+        let next = date.succ_opt().unwrap();
+        let at = |d: chrono::NaiveDate, h, m| d.and_hms_opt(h, m, 0).unwrap();
+        for h in 9..17 {
+            stats.record_session(SessionKind::Work, at(date, h, 0), at(date, h, 25), None, false);
+        }
+        stats.record_session(SessionKind::Work, at(next, 9, 0), at(next, 9, 25), Some("周报".to_string()), false);
+        stats.record_session(SessionKind::Rest, at(next, 9, 25), at(next, 9, 27), None, true);
+        let recent = stats.recent_sessions(3);
+        assert_eq!(recent.len(), 3);
+        assert_eq!(recent[0].start, chrono::NaiveTime::from_hms_opt(16, 0, 0).unwrap(), "前一天的最后一段");
+        assert_eq!(recent[1].task.as_deref(), Some("周报"));
+        assert!(recent[2].skipped);
+        assert_eq!(stats.recent_sessions(RECENT_SESSION_DOTS).len(), RECENT_SESSION_DOTS);
+
+        let (_tx, rx) = mpsc::channel();
+        let mut app = RestReminderApp::from_parts(AppConfig::default(), rx, None);
+        app.start_work();
+        app.time_remaining = Duration::ZERO;
+        app.tick();
+        app.skip_rest();
+        let skipped: Vec<_> = app.stats.recent_sessions(2).iter().map(|b| b.skipped).collect();
+        assert_eq!(skipped, vec![false, true]);
+        // 旧的 stats.json 没有 skipped 字段
+        let old: SessionBlock = serde_json::from_str(r#"{"kind":"Rest","start":"09:00:00","end":"09:05:00","task":null}"#).unwrap();
+        assert!(!old.skipped && !old.in_progress);
+    }
+}
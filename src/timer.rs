@@ -0,0 +1,2257 @@
+//! 专注/休息计时的状态机。不依赖窗口，测试里直接驱动 tick() 即可
+
+use eframe::egui;
+use std::sync::mpsc::Receiver;
+use std::time::{Duration, Instant, SystemTime};
+use tray_icon::menu::{CheckMenuItem, Menu, MenuItem, Submenu};
+use tray_icon::TrayIcon;
+
+use crate::*;
+
+pub(crate) const HOLD_TO_SKIP: Duration = Duration::from_secs(2); // 按住跳过需要的时长
+pub(crate) const OVERLAY_PREVIEW: Duration = Duration::from_secs(5); // 设置里预览休息界面的时长
+pub(crate) const SLEEP_GAP: Duration = Duration::from_secs(120); // 两帧之间超过这么久视为系统睡眠过
+pub(crate) const OVERLAY_FADE: Duration = Duration::from_millis(300);
+pub(crate) const DEFAULT_OVERLAY_TITLE: &str = "☕ 休息时间";
+
+// 跳过休息前的现场，撤销时原样恢复 (统计和任务队列也回到跳过前)
+pub(crate) struct SkipUndo {
+    pub(crate) until: Instant,
+    remaining: Duration,
+    rest_elapsed: Duration,
+    session_started_at: Option<chrono::NaiveDateTime>,
+    stats: Stats,
+    consecutive_skips: u32,
+    last_skip_at: Option<Instant>,
+    current_task: String,
+    task_queue: Vec<String>,
+    session_snoozes: u32,
+    logged: Option<Vec<u8>>, // 跳过时写入流水的键，撤销时删掉
+}
+
+// 没有设置任务时 {task} 替换为空，并去掉多余的空白
+pub(crate) fn expand_overlay_title(template: &str, task: &str) -> String {
+    let title = template.replace("{task}", task.trim());
+    let title = title.trim();
+    if title.is_empty() { DEFAULT_OVERLAY_TITLE.to_string() } else { title.to_string() }
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub(crate) enum AppState {
+    Ready,   // 尚未开始 (启动后或一轮休息结束后)
+    Working,
+    Resting,
+    Paused,  // 专注/休息中途暂停，可以继续
+}
+
+impl AppState {
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            AppState::Ready => "准备开始",
+            AppState::Working => "专注中",
+            AppState::Resting => "休息中",
+            AppState::Paused => "已暂停",
+        }
+    }
+}
+
+pub(crate) struct RestReminderApp {
+    pub(crate) state: AppState,
+    paused_from: AppState, // 暂停前所处的阶段，用于继续
+    pub(crate) config: AppConfig,
+    pub(crate) stats: Stats,
+    pub(crate) session_log: SessionLog,
+    pub(crate) session_totals: Option<(chrono::NaiveDate, SessionTotals, SessionTotals)>, // 统计面板显示的今日/本周流水汇总，写入新记录后重算
+    pub(crate) recent_notes: Vec<SessionNote>,
+    pub(crate) pending_note: Option<SessionNote>, // 等待填写的专注记录，note 字段即输入框内容
+    pub(crate) pending_celebration: Option<u64>,  // 达成的里程碑，回到主界面时放一次表情礼花
+    pub(crate) celebration: Option<(u64, Instant)>, // 正在显示的里程碑祝贺
+    pub(crate) work_done: Option<(Instant, bool)>, // 刚完成专注的时间，以及表情是否已经放过
+    first_session_lock: Option<chrono::NaiveDate>, // 当天第一个番茄锁定中，记录锁定的日期
+    last_app_sample: Instant,
+    last_clock_check: (Instant, SystemTime), // 上一帧的单调时钟与系统时钟，用于识别睡眠唤醒
+    pub(crate) completed_work_sessions: u64, // 本轮 (距上次长休息) 已完成的番茄数
+    pub(crate) is_long_rest: bool,
+    pub(crate) start_time: Option<Instant>,
+    pub(crate) time_remaining: Duration,
+    
+    pub(crate) work_input: String,
+    pub(crate) rest_input: String,
+    pub(crate) long_rest_input: String,
+    pub(crate) cycles_input: String,
+    pub(crate) idle_hide_input: String,
+    pub(crate) daily_cap_input: String,
+    pub(crate) autosave_input: String,
+    pub(crate) skip_nag_input: String,
+    pub(crate) paused_nudge_input: String,
+    pub(crate) skip_cooldown_input: String,
+    pub(crate) skip_undo_input: String,
+    pub(crate) snooze_input: String,
+    pub(crate) max_snoozes_input: String,
+    pub(crate) webhook_input: String,
+    pub(crate) work_cmd_input: String,
+    pub(crate) rest_cmd_input: String,
+    pub(crate) overlay_title_input: String,
+    pub(crate) tooltip_template_input: String,
+    pub(crate) current_task: String,
+    pub(crate) drops: Vec<EmojiDrop>,
+    pub(crate) last_frame: Instant,
+    pub(crate) breathing_secs: f32, // 呼吸引导在当前周期内的进度
+    overlay_shown_at: Instant,         // 蒙层开始显示的时间，用于淡入
+    overlay_fade_out: Option<Instant>, // 跳过休息后正在淡出
+    pub(crate) overlay_button_area: Option<egui::Rect>, // 上一帧蒙层按钮的区域，穿透模式下这里保持可点
+    pub(crate) click_through: bool,                     // 窗口当前是否处于鼠标穿透状态
+    pub(crate) last_interaction: Instant,
+
+    pub(crate) is_initialized: bool,
+    pub(crate) should_fullscreen: bool,
+    pub(crate) was_fullscreen: bool,
+    pub(crate) overlay_style: OverlayStyle, // 当前蒙层的窗口形式，退出时按同样的方式恢复
+    pub(crate) secondary_monitors: Vec<MonitorRect>, // 蒙层期间另外盖上休息画面的显示器
+    pub(crate) secondary_placed: Vec<bool>,          // 上面每个显示器的窗口是否已经按物理像素摆好
+    pub(crate) monitor_count: usize, // 启动、展开设置、进入蒙层时更新，设置里不必每帧枚举显示器
+    pub(crate) awaiting_ack: bool,       // 休息已结束，等用户确认后再开始下一轮
+    pub(crate) open_ended: bool,         // 本次休息不限时，开始休息时按配置决定
+    pub(crate) session_snoozes: u32,     // 这一轮休息已经推迟的次数，休息结束或重新开始专注时清零
+    pub(crate) overlay_preview_until: Option<Instant>, // 正在预览休息界面，到这个时间自动结束；不影响计时和统计
+    skip_held_since: Option<Instant>,      // 开启按住跳过时，跳过按钮从这个时间开始被按住
+    snooze_held_since: Option<Instant>,    // 同上，推迟按钮也要按住
+    rest_elapsed: Duration,   // 本次休息已经休息的时长 (不含暂停)
+    pub(crate) focused_widget: Option<egui::Id>, // 上一帧有键盘焦点的控件
+    auto_minimize_at: Option<Instant>, // 开始专注后到这个时间自动收起窗口
+    pub(crate) mini_overlay_pos: Option<egui::Pos2>, // 小窗当前的位置，小窗关闭时写回配置
+    pub(crate) is_overlay_mode: bool,
+    pub(crate) is_rest_toast: bool,
+    pub(crate) was_rest_toast: bool,
+    pub(crate) should_minimize: bool,
+    pub(crate) should_hide: bool,
+    
+    pub(crate) should_show_from_tray: bool,
+    pub(crate) auto_start_enabled: bool,
+    pub(crate) should_quit: bool,
+    pub(crate) off_hours: bool,
+    pub(crate) quiet_hours: bool, // 当前处于夜间静音时段
+    paused_by_lock: bool,
+    pub(crate) show_whats_new: bool,
+    pub(crate) error_toast: Option<(String, Instant)>, // 短暂显示在主界面顶部的错误提示
+    pub(crate) startup_warnings: Vec<String>, // 启动时的非致命问题，显示到用户点"知道了"为止
+    pub(crate) dev_mode: bool, // debug 构建或 --dev 启动时显示测试用的快进按钮
+    pub(crate) wayland: bool,  // Linux Wayland 会话，窗口无法主动抢焦点
+
+    pub(crate) tray_receiver: Receiver<TrayMessage>,
+    // 必须持有这些对象，否则托盘图标会消失 (测试环境下为 None)
+    pub(crate) _tray_icon: Option<TrayIcon>,
+    pub(crate) tray_icon_color: Option<[u8; 3]>, // 托盘图标当前的颜色，变化时才重绘
+    pub(crate) tray_tooltip: (String, Instant),  // 上次设置的托盘提示和时间，每秒最多更新一次
+    _tray_menu: Option<Menu>,
+    pub(crate) tray_presets: Option<Submenu>,
+    pub(crate) tray_status: Option<MenuItem>,  // 菜单顶部不可点的状态行
+    pub(crate) tray_status_state: Option<AppState>, // 状态行当前显示的状态，变化时才改文字
+    fullscreen_check: Option<(Instant, bool)>, // 上次检测全屏的时间和结果，每秒最多检测一次
+    pub(crate) consecutive_skips: u32,        // 连续跳过的休息次数，完整休息一次后清零
+    last_skip_at: Option<Instant>, // 上次跳过休息的时间，用于跳过后的冷却
+    pub(crate) skip_undo: Option<SkipUndo>,   // 刚跳过的休息，撤销窗口内可以恢复
+    paused_at: Option<Instant>,    // 真正进入暂停的时刻 (睡眠导致的暂停记睡眠开始时)，只用于主界面显示"已暂停 N 分钟"，催促后不重置
+    pub(crate) confirm_auto_start: Option<bool>, // 开机自启确认框打开中，值为"不再提示"是否勾选
+    last_stats_autosave: Instant,
+    autosaved: (u64, AppState, u64, u64), // 上次自动保存时的 autosave_fingerprint，没有变化就不再保存
+    skip_nag: bool,                // 本次休息因连续跳过而不可跳过
+    paused_since: Option<(AppState, Instant)>, // 催促的计时起点: 进入暂停/未开始或上次催促的时间；状态变了或催促一次后重新计时，显示用 paused_at
+    pub(crate) nudge_until: Option<Instant>,  // 催促时托盘图标闪烁到这个时间
+    pub(crate) tray_preset_items: Vec<CheckMenuItem>,
+    pub(crate) preset_name_input: String,
+    pub(crate) available_sounds: Vec<String>, // 声音文件夹里扫描到的文件
+    pub(crate) audio_devices: Option<Result<Vec<String>, String>>, // 声音诊断里列出的输出设备，点刷新时才扫描
+    session_minutes: u64,          // 本次专注的时长，快速开始时与配置不同
+    reminder_cursor: usize,        // 下一次从第几个提醒开始找
+    session_started_at: Option<chrono::NaiveDateTime>, // 当前专注/休息开始的本地时间，记时间轴用
+    pub(crate) task_queue: Vec<String>,       // 接下来要做的任务，休息结束后取第一个作为当前任务
+    pub(crate) task_queue_input: String,
+    clock_offset: Duration,        // 假时钟偏移，只有测试会修改，正常运行时始终为 0
+    pub(crate) current_reminder: Option<ReminderType>, // 本次休息的提醒类型
+    pub(crate) quick_start_input: String,
+    pub(crate) sounds_dir_input: String,
+}
+
+// 按住按钮的进度: 松开时清零，按满 HOLD_TO_SKIP 返回 1.0 并清零，下一次重新计时
+fn hold_progress(now: Instant, held_since: &mut Option<Instant>, held: bool) -> f32 {
+    if !held {
+        *held_since = None;
+        return 0.0;
+    }
+    let since = *held_since.get_or_insert(now);
+    let progress = progress_fraction(now.saturating_duration_since(since), HOLD_TO_SKIP);
+    if progress >= 1.0 {
+        *held_since = None;
+    }
+    progress
+}
+
+// 进度统一从这里算，结果限制在 [0, 1]；总时长为 0 时视为已完成，不会除以 0 得到 NaN
+pub(crate) fn progress_fraction(done: Duration, total: Duration) -> f32 {
+    if total.is_zero() {
+        return 1.0;
+    }
+    (done.as_secs_f32() / total.as_secs_f32()).clamp(0.0, 1.0)
+}
+
+// 一小时以内显示 MM:SS，超过一小时显示 HH:MM:SS
+pub(crate) fn format_duration(duration: Duration) -> String {
+    let total = duration.as_secs();
+    let (hours, minutes, seconds) = (total / 3600, total / 60 % 60, total % 60);
+    if hours > 0 {
+        format!("{:02}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{:02}:{:02}", minutes, seconds)
+    }
+}
+
+// 粗略的已过时长: 不到一分钟、N 分钟、H 小时 M 分钟
+pub(crate) fn format_elapsed_minutes(elapsed: Duration) -> String {
+    let minutes = elapsed.as_secs() / 60;
+    match minutes {
+        0 => "不到 1 分钟".to_string(),
+        1..=59 => format!("{} 分钟", minutes),
+        _ => format!("{} 小时 {} 分钟", minutes / 60, minutes % 60),
+    }
+}
+
+// 倒计时结束的本地时刻，不在今天时标注 (明天)
+pub(crate) fn format_end_time(now: chrono::NaiveDateTime, remaining: Duration, h24: bool) -> String {
+    let end = chrono::Duration::from_std(remaining).ok().and_then(|d| now.checked_add_signed(d)).unwrap_or(now);
+    let time = end.format(if h24 { "%H:%M" } else { "%-I:%M %p" }).to_string();
+    if end.date() == now.date() { time } else { format!("{} (明天)", time) }
+}
+
+// 到下一个整 step 分钟 (半点/整点) 的时长；不足一分钟时顺延到再下一个
+pub(crate) fn until_next_boundary(now: chrono::NaiveDateTime, step_minutes: u32) -> Duration {
+    use chrono::Timelike;
+    let step = u64::from(step_minutes.max(1)) * 60;
+    let elapsed = u64::from(now.minute() * 60 + now.second()) % step;
+    let mut secs = step - elapsed;
+    if secs < 60 {
+        secs += step;
+    }
+    Duration::from_secs(secs)
+}
+
+impl RestReminderApp {
+    // 不依赖 eframe::CreationContext 的构造函数，tray 为 None 时可在测试中无窗口构造。
+    // 测试写法: from_parts(AppConfig::default(), rx, None) 得到不读写磁盘、没有托盘的实例，
+    // 用 set_state_for_test 直接进入某个状态，用 advance_clock 拨快假时钟后调用 tick()
+    pub(crate) fn from_parts(config: AppConfig, tray_receiver: Receiver<TrayMessage>, tray: Option<(TrayIcon, Menu, Submenu, MenuItem)>) -> Self {
+        let (tray_icon, tray_menu, tray_presets, tray_status) = match tray {
+            Some((icon, menu, presets, status)) => (Some(icon), Some(menu), Some(presets), Some(status)),
+            None => (None, None, None, None),
+        };
+        let show_whats_new = !config.last_seen_version.is_empty() && config.last_seen_version != APP_VERSION;
+        let available_sounds = config.resolved_sounds_dir().map(|dir| scan_sounds(&dir)).unwrap_or_default();
+
+        Self {
+            state: AppState::Ready,
+            paused_from: AppState::Working,
+            completed_work_sessions: 0,
+            is_long_rest: false,
+            start_time: None,
+            time_remaining: minutes_to_duration(config.work_minutes),
+            work_input: config.work_minutes.to_string(),
+            rest_input: config.rest_minutes.to_string(),
+            long_rest_input: config.long_rest_minutes.to_string(),
+            cycles_input: config.cycles_before_long_rest.to_string(),
+            idle_hide_input: duration_input_text(config.auto_hide_when_idle_secs),
+            daily_cap_input: config.daily_work_cap_minutes.to_string(),
+            autosave_input: config.stats_autosave_secs.to_string(),
+            skip_nag_input: config.skip_nag_threshold.to_string(),
+            paused_nudge_input: config.paused_nudge_minutes.to_string(),
+            skip_cooldown_input: config.skip_cooldown_secs.to_string(),
+            skip_undo_input: config.skip_undo_secs.to_string(),
+            snooze_input: config.snooze_minutes.to_string(),
+            max_snoozes_input: config.max_snoozes.to_string(),
+            webhook_input: config.webhook_url.clone().unwrap_or_default(),
+            work_cmd_input: config.on_work_end_cmd.clone().unwrap_or_default(),
+            rest_cmd_input: config.on_rest_end_cmd.clone().unwrap_or_default(),
+            overlay_title_input: config.overlay_title.clone(),
+            tooltip_template_input: config.tooltip_template.clone(),
+            current_task: config.current_task.clone(),
+            stats: Stats::default(),
+            session_log: SessionLog::default(),
+            session_totals: None,
+            recent_notes: vec![],
+            pending_note: None,
+            pending_celebration: None,
+            celebration: None,
+            work_done: None,
+            first_session_lock: None,
+            last_app_sample: Instant::now(),
+            last_clock_check: (Instant::now(), SystemTime::now()),
+            drops: vec![],
+            last_frame: Instant::now(),
+            breathing_secs: 0.0,
+            overlay_shown_at: Instant::now(),
+            overlay_fade_out: None,
+            overlay_button_area: None,
+            click_through: false,
+            last_interaction: Instant::now(),
+            
+            is_initialized: false,
+            should_fullscreen: false,
+            was_fullscreen: false,
+            overlay_style: OverlayStyle::Fullscreen,
+            secondary_monitors: vec![],
+            secondary_placed: vec![],
+            monitor_count: list_monitors().len(),
+            awaiting_ack: false,
+            open_ended: false,
+            session_snoozes: 0,
+            overlay_preview_until: None,
+            skip_held_since: None,
+            snooze_held_since: None,
+            rest_elapsed: Duration::ZERO,
+            focused_widget: None,
+            auto_minimize_at: None,
+            mini_overlay_pos: None,
+            is_overlay_mode: false,
+            is_rest_toast: false,
+            was_rest_toast: false,
+            should_minimize: false,
+            should_hide: false,
+            should_show_from_tray: false,
+            auto_start_enabled: check_auto_start(),
+            should_quit: false,
+            off_hours: false,
+            quiet_hours: false,
+            paused_by_lock: false,
+            show_whats_new,
+            error_toast: None,
+            startup_warnings: vec![],
+            dev_mode: cfg!(debug_assertions) || std::env::args().any(|arg| arg == "--dev"),
+            wayland: is_wayland_session(
+                std::env::var("XDG_SESSION_TYPE").ok().as_deref(),
+                std::env::var_os("WAYLAND_DISPLAY").is_some(),
+            ),
+
+            tray_receiver,
+            _tray_icon: tray_icon,
+            tray_icon_color: None,
+            tray_tooltip: (TRAY_TOOLTIP.to_string(), Instant::now()),
+            _tray_menu: tray_menu,
+            tray_presets,
+            tray_status,
+            tray_status_state: None,
+            fullscreen_check: None,
+            consecutive_skips: 0,
+            last_skip_at: None,
+            skip_undo: None,
+            paused_at: None,
+            confirm_auto_start: None,
+            last_stats_autosave: Instant::now(),
+            autosaved: (0, AppState::Ready, minutes_to_duration(config.work_minutes).as_secs(), 0),
+            skip_nag: false,
+            paused_since: None,
+            nudge_until: None,
+            tray_preset_items: vec![],
+            preset_name_input: String::new(),
+            available_sounds,
+            audio_devices: None,
+            session_minutes: config.work_minutes,
+            reminder_cursor: 0,
+            session_started_at: None,
+            task_queue: config.task_queue.clone(),
+            task_queue_input: String::new(),
+            clock_offset: Duration::ZERO,
+            current_reminder: None,
+            quick_start_input: String::new(),
+            sounds_dir_input: config.sounds_dir.clone(),
+            config,
+        }
+    }
+
+    // 输入框还没提交就点了开始时，先按输入框里的内容生效；无效的输入丢弃，沿用上次的有效值。
+    // 仍有焦点的输入框说明用户还在输入，不去动它
+    fn commit_pending_durations(&mut self) {
+        let before = (self.config.work_minutes, self.config.rest_minutes);
+        let editing = |label| self.focused_widget == Some(settings_field_id(label));
+        let (edit_work, edit_rest) = (editing("专注时长(分):"), editing("休息时长(分):"));
+        if !edit_work {
+            if let Ok(minutes) = parse_minutes(self.work_input.trim()) {
+                self.config.set_work_minutes(minutes);
+            }
+            self.work_input = self.config.work_minutes.to_string();
+        }
+        if !edit_rest {
+            if let Ok(minutes) = parse_minutes(self.rest_input.trim()) {
+                self.config.set_rest_minutes(minutes);
+            }
+            self.rest_input = self.config.rest_minutes.to_string();
+        }
+        if (self.config.work_minutes, self.config.rest_minutes) != before {
+            println!("开始前应用未提交的时长: 专注 {} 分钟，休息 {} 分钟", self.config.work_minutes, self.config.rest_minutes);
+            self.config.save();
+        }
+    }
+
+    // 用户点按钮、按快捷键或点托盘开始时用这两个；自动切换、启动时自动开始和对齐时钟都不碰输入框
+    pub(crate) fn user_start_work(&mut self) {
+        self.commit_pending_durations();
+        self.start_work();
+    }
+
+    pub(crate) fn user_start_rest(&mut self) {
+        self.commit_pending_durations();
+        self.start_rest();
+    }
+
+    pub(crate) fn start_work(&mut self) {
+        if self.abandon_work() && self.skip_cooldown_left().is_some() {
+            // 推迟中的休息被放弃等于跳过，跳过后的冷却同样生效
+            return self.reset_to_ready();
+        }
+        let date = today();
+        if self.config.lock_first_session && self.stats.completed_on(date) == 0 {
+            println!("今天的第一个番茄，锁定暂停和跳过");
+            self.first_session_lock = Some(date);
+        }
+        // 开始专注时保存任务，专注中途关掉程序重启后任务还在
+        self.persist_tasks();
+        self.session_snoozes = 0;
+        self.skip_undo = None;
+        self.paused_at = None;
+        self.state = AppState::Working;
+        self.start_time = Some(self.now());
+        self.session_started_at = Some(chrono::Local::now().naive_local());
+        self.session_minutes = self.config.work_minutes;
+        self.time_remaining = minutes_to_duration(self.session_minutes);
+        self.drops.clear();
+        self.should_fullscreen = false;
+        self.is_overlay_mode = false;
+        self.play_event_sound(&self.config.work_start_sound);
+        if self.config.auto_minimize_on_work {
+            self.auto_minimize_at = Some(self.now() + Duration::from_secs(self.config.auto_minimize_delay_secs));
+        }
+    }
+
+    // 一次性的定时器，到点时还在专注才收起；中途暂停或跳过就作废
+    pub(crate) fn check_auto_minimize(&mut self) {
+        let Some(at) = self.auto_minimize_at else { return };
+        if self.state != AppState::Working {
+            self.auto_minimize_at = None;
+        } else if self.now() >= at {
+            println!("开始专注 {} 秒后自动收起窗口", self.config.auto_minimize_delay_secs);
+            self.auto_minimize_at = None;
+            self.should_hide = true;
+        }
+    }
+
+    // 临时按指定时长专注一次，不修改配置；结束后下一轮恢复默认时长
+    pub(crate) fn start_quick(&mut self, minutes: u64) {
+        self.start_work();
+        if self.state != AppState::Working {
+            return;
+        }
+        self.session_minutes = minutes.clamp(1, MAX_SESSION_MINUTES);
+        self.time_remaining = minutes_to_duration(self.session_minutes);
+        println!("快速开始: 专注 {} 分钟", self.session_minutes);
+    }
+
+    // 专注到整点/半点: 剩余时间按墙上时钟算，不改配置里的专注时长
+    pub(crate) fn start_until(&mut self, remaining: Duration) {
+        self.start_work();
+        if self.state != AppState::Working {
+            return;
+        }
+        self.session_minutes = duration_to_minutes(remaining).clamp(1, MAX_SESSION_MINUTES);
+        self.time_remaining = remaining;
+        println!("专注到 {}", format_end_time(chrono::Local::now().naive_local(), remaining, true));
+    }
+
+    pub(crate) fn start_rest(&mut self) {
+        // 推迟中开始休息是回到推迟的那次休息，不算放弃
+        if self.session_snoozes == 0 {
+            self.abandon_work();
+        }
+        self.state = AppState::Resting;
+        self.start_time = Some(self.now());
+        self.session_started_at = Some(chrono::Local::now().naive_local());
+        let threshold = self.config.skip_nag_threshold;
+        self.skip_nag = threshold > 0 && self.consecutive_skips >= threshold;
+        if self.skip_nag {
+            println!("已连续跳过 {} 次休息，本次休息不可跳过", self.consecutive_skips);
+        }
+        // 推迟后再开始的是同一轮休息，长休息和健康提醒沿用推迟前的
+        if self.session_snoozes == 0 {
+            self.is_long_rest = self.long_rest_due();
+            if !self.is_long_rest && self.config.cap_forces_long_rest && self.over_daily_cap() {
+                println!("今日专注已超过上限，强制长休息");
+                self.is_long_rest = true;
+            } else if self.is_long_rest {
+                println!("已完成 {} 个番茄，进入长休息", self.completed_work_sessions);
+                self.completed_work_sessions = 0;
+            }
+            self.current_reminder = None;
+            if !self.is_long_rest {
+                if let Some(index) = next_reminder(&self.config.reminders, self.reminder_cursor) {
+                    self.current_reminder = Some(self.config.reminders[index].kind);
+                    self.reminder_cursor = index + 1;
+                }
+            }
+        }
+        self.time_remaining = minutes_to_duration(self.current_rest_minutes());
+        self.open_ended = self.config.open_ended_rest && self.config.intensity != Intensity::Strict;
+        self.rest_elapsed = Duration::ZERO;
+        self.drops.clear();
+        self.breathing_secs = 0.0;
+        self.show_rest_display();
+        self.play_event_sound(&self.config.break_sound);
+    }
+
+    // 只切换显示，不碰 state/start_time，所以专注计时在预览期间照常走
+    pub(crate) fn preview_overlay(&mut self) {
+        if self.state == AppState::Resting {
+            return;
+        }
+        println!("预览休息界面");
+        self.overlay_preview_until = Some(self.now() + OVERLAY_PREVIEW);
+        self.should_fullscreen = true;
+        self.is_overlay_mode = true;
+        self.overlay_shown_at = Instant::now();
+    }
+
+    pub(crate) fn check_overlay_preview(&mut self) {
+        if self.overlay_preview_until.is_some_and(|until| self.now() >= until) {
+            self.end_overlay_preview();
+        }
+    }
+
+    pub(crate) fn end_overlay_preview(&mut self) {
+        if self.overlay_preview_until.take().is_some() && self.state != AppState::Resting {
+            self.should_fullscreen = false;
+            self.is_overlay_mode = false;
+        }
+    }
+
+    // 按配置显示全屏蒙层或演示模式小窗
+    pub(crate) fn show_rest_display(&mut self) {
+        self.overlay_preview_until = None;
+        if self.quiet_hours {
+            println!("静音时段内开始休息，不显示休息提示");
+            self.should_fullscreen = false;
+            self.is_overlay_mode = false;
+            self.is_rest_toast = false;
+            return;
+        }
+        let presenting = match self.config.intensity {
+            Intensity::Gentle => true,
+            Intensity::Strict => false,
+            Intensity::Normal => self.config.presentation_mode || (self.config.auto_presentation_mode && media_device_in_use()),
+        };
+        if presenting {
+            println!("开始休息模式 (演示模式)，显示角落小窗提示");
+            self.should_fullscreen = false;
+            self.is_overlay_mode = false;
+            self.is_rest_toast = true;
+        } else {
+            println!("开始休息模式，准备显示全屏蒙版");
+            self.should_fullscreen = true;
+            self.is_overlay_mode = true;
+            self.is_rest_toast = false;
+            self.overlay_shown_at = Instant::now();
+        }
+
+        // 确保窗口可见
+        self.should_hide = false;
+    }
+
+    // 计时用的当前时间，测试可以通过 clock_offset 拨快
+    pub(crate) fn now(&self) -> Instant {
+        Instant::now() + self.clock_offset
+    }
+
+    pub(crate) fn pause(&mut self) {
+        if let Some(start) = self.start_time {
+            let elapsed = self.now().saturating_duration_since(start);
+            self.add_rest_elapsed(elapsed);
+            if self.open_ended && self.state == AppState::Resting {
+                // 不限时休息没有剩余时间可扣
+            } else if elapsed < self.time_remaining {
+                self.time_remaining -= elapsed;
+            } else {
+                self.time_remaining = Duration::ZERO;
+            }
+        }
+        self.start_time = None;
+        if matches!(self.state, AppState::Working | AppState::Resting) {
+            self.paused_from = self.state;
+        }
+        self.state = AppState::Paused;
+        self.paused_at = Some(self.now());
+        self.leave_rest_display();
+    }
+
+    // 只在暂停状态下有值；从未开始 (Ready) 时不显示
+    pub(crate) fn paused_for(&self) -> Option<Duration> {
+        let at = self.paused_at.filter(|_| self.state == AppState::Paused)?;
+        Some(self.now().saturating_duration_since(at))
+    }
+
+    // 一轮结束，回到尚未开始的状态，时间重置为专注时长
+    fn reset_to_ready(&mut self) {
+        self.first_session_lock = None;
+        self.awaiting_ack = false;
+        self.open_ended = false;
+        self.paused_at = None;
+        self.session_snoozes = 0;
+        self.state = AppState::Ready;
+        self.start_time = None;
+        self.time_remaining = minutes_to_duration(self.config.work_minutes);
+        self.leave_rest_display();
+    }
+
+    fn leave_rest_display(&mut self) {
+        self.overlay_preview_until = None;
+        self.overlay_fade_out = None;
+        self.last_interaction = Instant::now();
+        self.drops.clear();
+        self.should_fullscreen = false;
+        self.is_overlay_mode = false;
+        self.is_rest_toast = false;
+    }
+
+    // 从暂停处继续之前的阶段
+    pub(crate) fn resume(&mut self) {
+        self.paused_at = None;
+        match self.paused_from {
+            AppState::Resting => {
+                self.state = AppState::Resting;
+                self.start_time = Some(self.now());
+                self.show_rest_display();
+            }
+            _ => self.resume_work(),
+        }
+    }
+
+    fn add_rest_elapsed(&mut self, elapsed: Duration) {
+        if self.state == AppState::Resting {
+            let limit = if self.open_ended { elapsed } else { self.time_remaining };
+            self.rest_elapsed += elapsed.min(limit);
+        }
+    }
+
+    fn play_event_sound(&self, id: &str) {
+        if self.config.sound_enabled && !self.quiet_hours && !id.is_empty() {
+            play_sound(id, self.config.resolved_sounds_dir(), self.config.audio_device.clone());
+        }
+    }
+
+    pub(crate) fn over_daily_cap(&self) -> bool {
+        let cap = self.config.daily_work_cap_minutes;
+        cap > 0 && self.stats.day(today()).is_some_and(|day| day.focus_secs >= cap.saturating_mul(60))
+    }
+
+    // 本轮已完成几个番茄 / 每轮几个，例如 (2, 4)；关闭长休息时为 None
+    pub(crate) fn cycle_progress(&self) -> Option<(u64, u64)> {
+        let cycles = self.config.cycles_before_long_rest;
+        (cycles > 0).then(|| (self.completed_work_sessions.min(cycles), cycles))
+    }
+
+    fn long_rest_due(&self) -> bool {
+        let cycles = self.config.cycles_before_long_rest;
+        cycles > 0 && self.completed_work_sessions >= cycles
+    }
+
+    pub(crate) fn current_rest_minutes(&self) -> u64 {
+        if self.is_long_rest {
+            return self.config.long_rest_minutes;
+        }
+        let reminder = self.current_reminder.and_then(|kind| self.config.reminders.iter().find(|r| r.kind == kind));
+        match reminder {
+            Some(r) if r.minutes > 0 => r.minutes.min(MAX_SESSION_MINUTES),
+            _ => self.config.rest_minutes,
+        }
+    }
+
+    // 从暂停处继续专注，保留剩余时间
+    fn resume_work(&mut self) {
+        self.state = AppState::Working;
+        self.start_time = Some(self.now());
+        self.should_fullscreen = false;
+        self.is_overlay_mode = false;
+    }
+
+    // 锁屏时暂停正在进行的专注，解锁后自动继续
+    pub(crate) fn apply_session_lock(&mut self, locked: bool) {
+        if locked && self.state == AppState::Working {
+            println!("检测到锁屏，暂停专注");
+            self.pause();
+            self.paused_by_lock = true;
+        } else if !locked && self.paused_by_lock {
+            self.paused_by_lock = false;
+            if self.state == AppState::Paused {
+                println!("已解锁，继续专注");
+                self.resume_work();
+            }
+        }
+    }
+
+    // 跨日后锁定失效，重新按新的一天判断
+    pub(crate) fn check_first_session_rollover(&mut self, date: chrono::NaiveDate) {
+        if self.first_session_lock.is_some_and(|day| day != date) {
+            self.first_session_lock = None;
+        }
+    }
+
+    pub(crate) fn rest_title(&self) -> String {
+        if self.awaiting_ack {
+            return "✅ 休息结束".to_string();
+        }
+        if self.is_long_rest {
+            return "🌴 长休息时间".to_string();
+        }
+        if let Some(kind) = self.current_reminder {
+            return format!("{} {}", kind.icon(), kind.label());
+        }
+        expand_overlay_title(&self.config.overlay_title, &self.current_task)
+    }
+
+    pub(crate) fn skip_locked(&self) -> bool {
+        self.first_session_lock.is_some()
+    }
+
+    // 只改时长，不开始计时；未开始时同步更新显示的倒计时
+    pub(crate) fn apply_preset(&mut self, index: usize) {
+        if !self.config.apply_preset(index) {
+            println!("预设 {} 不存在", index);
+            return;
+        }
+        println!("切换到预设: {}", self.config.presets[index].label());
+        self.config.save();
+        self.sync_inputs_from_config();
+        if self.state == AppState::Ready {
+            self.time_remaining = minutes_to_duration(self.config.work_minutes);
+        }
+        self.rebuild_tray_presets();
+    }
+
+    // 休息不能跳过的原因；None 表示可以跳过
+    pub(crate) fn rest_lock_reason(&self) -> Option<String> {
+        if self.skip_locked() {
+            Some("🔒 今天的第一个番茄，休息不可跳过".to_string())
+        } else if self.config.intensity == Intensity::Strict {
+            Some("🔒 严格模式，休息不可跳过".to_string())
+        } else if self.skip_nag {
+            Some(format!("连续跳过了{}次休息，这次请休息一下", self.consecutive_skips))
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn toggle_pause(&mut self) {
+        if self.state == AppState::Resting && self.rest_lock_reason().is_some() {
+            return;
+        }
+        match self.state {
+            AppState::Working | AppState::Resting => self.pause(),
+            AppState::Paused => self.resume(),
+            AppState::Ready if self.skip_cooldown_left().is_none() => self.user_start_work(),
+            AppState::Ready => println!("跳过休息后的冷却中，暂不开始专注"),
+        }
+    }
+
+    // 和界面上的按钮遵守同样的锁定规则: 按钮不显示时快捷键也不生效
+    pub(crate) fn apply_shortcut(&mut self, action: ShortcutAction) {
+        if self.skip_locked() || self.off_hours {
+            return;
+        }
+        match action {
+            ShortcutAction::TogglePause => self.toggle_pause(),
+            ShortcutAction::Reset if self.state != AppState::Resting => {
+                self.abandon_work();
+                self.reset_to_ready();
+            }
+            ShortcutAction::SkipRest if self.awaiting_ack => self.acknowledge_break(false),
+            // 开启按住跳过时 Esc 不能绕过按住
+            ShortcutAction::SkipRest
+                if self.state == AppState::Resting && (self.open_ended_rest_done() || (self.rest_lock_reason().is_none() && !self.config.hold_to_skip)) =>
+            {
+                self.skip_rest()
+            }
+            ShortcutAction::Reset | ShortcutAction::SkipRest => {}
+        }
+    }
+
+    // 不限时休息由用户决定何时结束，但至少休息满平时的休息时长才算正常完成 (也不受跳过锁定限制)；
+    // 不满时结束和普通休息一样算跳过
+    pub(crate) fn open_ended_rest_done(&self) -> bool {
+        self.open_ended && self.state == AppState::Resting && self.rest_elapsed >= minutes_to_duration(self.current_rest_minutes())
+    }
+
+    // 跳过休息后的冷却还剩多久；没有冷却时返回 None
+    pub(crate) fn skip_cooldown_left(&self) -> Option<Duration> {
+        let cooldown = Duration::from_secs(self.config.skip_cooldown_secs);
+        let since = self.now().saturating_duration_since(self.last_skip_at?);
+        (since < cooldown).then(|| cooldown - since)
+    }
+
+    // 每帧报告跳过按钮是否仍被按住，返回按住的进度；按满时跳过，提前松开从头开始
+    pub(crate) fn hold_skip(&mut self, held: bool) -> f32 {
+        let progress = hold_progress(self.now(), &mut self.skip_held_since, held);
+        if progress >= 1.0 {
+            self.skip_rest();
+        }
+        progress
+    }
+
+    // 推迟同样能让这次休息不了了之，开启按住跳过时也要按满才推迟
+    pub(crate) fn hold_snooze(&mut self, held: bool) -> f32 {
+        let progress = hold_progress(self.now(), &mut self.snooze_held_since, held);
+        if progress >= 1.0 {
+            self.snooze_rest();
+        }
+        progress
+    }
+
+    pub(crate) fn snooze_limit_reached(&self) -> bool {
+        self.session_snoozes >= self.config.max_snoozes
+    }
+
+    // 推迟时关掉休息提示，再专注 snooze_minutes 后开始同一轮休息；推迟的这段不算一个番茄
+    pub(crate) fn snooze_rest(&mut self) {
+        if self.state != AppState::Resting || self.awaiting_ack || self.rest_lock_reason().is_some() || self.snooze_limit_reached() {
+            return;
+        }
+        // 倒计时恰好走完时 tick 已经正常结束了休息，不再算推迟
+        self.tick();
+        if self.awaiting_ack || self.state != AppState::Resting {
+            return;
+        }
+        self.stats.record_rest(today(), self.rest_elapsed.as_secs());
+        self.stats.record_snooze(today());
+        self.stats.save();
+        self.session_snoozes += 1;
+        println!("推迟休息 {} 分钟 (本轮第 {} 次)", self.config.snooze_minutes, self.session_snoozes);
+        self.state = AppState::Working;
+        self.start_time = Some(self.now());
+        self.time_remaining = minutes_to_duration(self.config.snooze_minutes);
+        self.should_minimize = true;
+        self.leave_rest_display();
+    }
+
+    // 开发测试用：把当前阶段剩余时间改为 2 秒，快速查看阶段切换
+    pub(crate) fn fast_forward(&mut self) {
+        if self.start_time.is_some() {
+            self.time_remaining = Duration::from_secs(2);
+            self.start_time = Some(self.now());
+        }
+    }
+
+    // 开启平滑过渡时先淡出蒙层，淡出结束 (或淡出中再次点击) 才真正跳过
+    pub(crate) fn skip_rest(&mut self) {
+        if self.awaiting_ack {
+            // 休息已经完整结束，不算跳过
+            return self.acknowledge_break(false);
+        }
+        if self.config.smooth_transitions && self.is_overlay_mode && self.overlay_fade_out.is_none() {
+            self.overlay_fade_out = Some(Instant::now());
+            return;
+        }
+        if self.state == AppState::Resting {
+            // 先把到此刻为止的休息时长记上；倒计时恰好走完时 tick 已经正常结束了休息
+            self.tick();
+            if self.awaiting_ack {
+                return self.acknowledge_break(false);
+            } else if self.state != AppState::Resting {
+                return;
+            }
+        }
+        if self.open_ended_rest_done() {
+            return self.complete_rest();
+        }
+        let undo_secs = self.config.skip_undo_secs;
+        if self.state == AppState::Resting && undo_secs > 0 {
+            self.skip_undo = Some(SkipUndo {
+                until: self.now() + Duration::from_secs(undo_secs),
+                remaining: self.time_remaining,
+                rest_elapsed: self.rest_elapsed,
+                session_started_at: self.session_started_at,
+                stats: self.stats.clone(),
+                consecutive_skips: self.consecutive_skips,
+                last_skip_at: self.last_skip_at,
+                current_task: self.current_task.clone(),
+                task_queue: self.task_queue.clone(),
+                session_snoozes: self.session_snoozes,
+                logged: None,
+            });
+        }
+        if self.state == AppState::Resting {
+            self.stats.record_rest(today(), self.rest_elapsed.as_secs());
+            self.consecutive_skips += 1;
+            self.last_skip_at = Some(self.now());
+            self.stats.record_skipped_rest(today());
+            let logged = self.record_session(SessionKind::Rest, true);
+            if let Some(undo) = &mut self.skip_undo {
+                undo.logged = logged;
+            }
+            self.stats.save();
+            self.advance_task_queue();
+        }
+        // 可撤销时先不收起窗口，撤销提示要留在眼前；过了撤销时间再收起
+        self.should_minimize = self.skip_undo.is_none();
+        self.reset_to_ready();
+    }
+
+    pub(crate) fn undo_skip(&mut self) {
+        let Some(undo) = self.skip_undo.take() else { return };
+        if self.state != AppState::Ready {
+            return;
+        }
+        println!("撤销跳过，继续休息");
+        self.stats = undo.stats;
+        self.stats.save();
+        if let Some(key) = undo.logged {
+            self.session_log.remove(&key);
+            self.session_totals = None;
+        }
+        self.consecutive_skips = undo.consecutive_skips;
+        self.last_skip_at = undo.last_skip_at;
+        self.current_task = undo.current_task;
+        self.task_queue = undo.task_queue;
+        self.persist_tasks();
+        self.session_snoozes = undo.session_snoozes;
+        self.session_started_at = undo.session_started_at;
+        self.rest_elapsed = undo.rest_elapsed;
+        self.state = AppState::Resting;
+        self.time_remaining = undo.remaining;
+        self.start_time = Some(self.now());
+        self.show_rest_display();
+    }
+
+    pub(crate) fn check_skip_undo(&mut self) {
+        if self.skip_undo.as_ref().is_some_and(|undo| self.now() >= undo.until) {
+            self.skip_undo = None;
+            if self.state == AppState::Ready {
+                self.should_minimize = true;
+            }
+        }
+    }
+
+    // 结束当前这一段，写入时间轴 (调用方负责保存) 和流水，返回流水的键
+    fn record_session(&mut self, kind: SessionKind, skipped: bool) -> Option<Vec<u8>> {
+        let start = self.session_started_at.take()?;
+        let end = chrono::Local::now().naive_local();
+        let task = self.current_task.trim();
+        let task = (!task.is_empty()).then(|| task.to_string());
+        self.stats.record_session(kind, start, end, task, skipped);
+        let duration = match kind {
+            SessionKind::Rest => self.rest_elapsed,
+            SessionKind::Work => self.work_elapsed_now(),
+        };
+        self.session_totals = None;
+        self.session_log.record(&SessionRecord { kind, start, end, duration_secs: duration.as_secs(), finished: !skipped })
+    }
+
+    // 专注中途重新开始、重置或提前休息时，这一段没有完成，记为放弃的专注。
+    // 推迟休息期间也是 Working，这时放弃的是推迟的那次休息，按跳过休息记录；返回是否放弃了休息
+    fn abandon_work(&mut self) -> bool {
+        let working = self.state == AppState::Working || (self.state == AppState::Paused && self.paused_from == AppState::Working);
+        if !working {
+            return false;
+        }
+        if self.session_snoozes > 0 {
+            println!("放弃了推迟中的休息，记为跳过");
+            self.session_snoozes = 0;
+            self.consecutive_skips += 1;
+            self.last_skip_at = Some(self.now());
+            self.stats.record_skipped_rest(today());
+            self.record_session(SessionKind::Rest, true);
+            self.stats.save();
+            return true;
+        }
+        if self.session_started_at.is_some() {
+            println!("放弃了进行中的专注");
+            self.record_session(SessionKind::Work, true);
+            self.stats.save();
+        }
+        false
+    }
+
+    // 当前阶段到此刻为止的剩余时间，不修改计时状态
+    fn remaining_now(&self) -> Duration {
+        let elapsed = self.start_time.map_or(Duration::ZERO, |start| self.now().saturating_duration_since(start));
+        self.time_remaining.saturating_sub(elapsed)
+    }
+
+    fn work_elapsed_now(&self) -> Duration {
+        minutes_to_duration(self.session_minutes).saturating_sub(self.remaining_now())
+    }
+
+    // 本次休息到此刻为止的时长，和 add_rest_elapsed 的算法一致
+    fn rest_elapsed_now(&self) -> Duration {
+        let running = self.start_time.filter(|_| self.state == AppState::Resting);
+        let running = running.map_or(Duration::ZERO, |start| self.now().saturating_duration_since(start));
+        let limit = if self.open_ended { running } else { self.time_remaining };
+        self.rest_elapsed + running.min(limit)
+    }
+
+    // 要保存的统计: 正在进行 (或暂停中) 的这一段先按"到现在为止"记进时间轴并计入专注/休息时长，标为进行中，
+    // 内存里的统计不变；程序崩溃时已经专注的部分还在，正常结束时会被完整的记录覆盖。推迟休息期间不算任何一段
+    fn stats_snapshot(&self) -> Stats {
+        let mut snapshot = self.stats.clone();
+        let phase = if self.state == AppState::Paused { self.paused_from } else { self.state };
+        let kind = match phase {
+            AppState::Working if self.session_snoozes == 0 => SessionKind::Work,
+            AppState::Resting => SessionKind::Rest,
+            _ => return snapshot,
+        };
+        let Some(start) = self.session_started_at else { return snapshot };
+        let task = self.current_task.trim();
+        let task = (!task.is_empty()).then(|| task.to_string());
+        snapshot.record_session(kind, start, chrono::Local::now().naive_local(), task, false);
+        if let Some(block) = snapshot.days.get_mut(&start.date()).and_then(|day| day.sessions.last_mut()) {
+            block.in_progress = true;
+        }
+        match kind {
+            SessionKind::Work => snapshot.days.entry(today()).or_default().focus_secs += self.work_elapsed_now().as_secs(),
+            SessionKind::Rest => snapshot.record_rest(today(), self.rest_elapsed_now().as_secs()),
+        }
+        snapshot
+    }
+
+    // 统计保存的次数、状态和进行中这一段的进度都没变时，磁盘上的内容已经是最新的
+    fn autosave_fingerprint(&self) -> (u64, AppState, u64, u64) {
+        let generation = STATS_GENERATION.load(std::sync::atomic::Ordering::SeqCst);
+        (generation, self.state, self.remaining_now().as_secs(), self.rest_elapsed_now().as_secs())
+    }
+
+    // 返回是否真的保存了
+    pub(crate) fn check_stats_autosave(&mut self) -> bool {
+        let interval = self.config.stats_autosave_secs;
+        if interval == 0 || self.now().saturating_duration_since(self.last_stats_autosave) < Duration::from_secs(interval) {
+            return false;
+        }
+        self.last_stats_autosave = self.now();
+        if self.autosave_fingerprint() == self.autosaved {
+            return false;
+        }
+        self.stats_snapshot().save_in_background();
+        self.autosaved = self.autosave_fingerprint();
+        true
+    }
+
+    pub(crate) fn check_overlay_fade_out(&mut self) {
+        if self.overlay_fade_out.is_some_and(|at| at.elapsed() >= OVERLAY_FADE) {
+            self.skip_rest();
+        }
+    }
+
+    // 蒙层当前的不透明度；只影响绘制，按钮在淡入过程中始终可点
+    pub(crate) fn overlay_opacity(&self) -> f32 {
+        if !self.config.smooth_transitions {
+            return 1.0;
+        }
+        let progress = |at: Instant| progress_fraction(at.elapsed(), OVERLAY_FADE);
+        match self.overlay_fade_out {
+            Some(at) => 1.0 - progress(at),
+            None => progress(self.overlay_shown_at),
+        }
+    }
+
+    // 专注结束时其它程序正全屏 (游戏/视频) 就先不休息，保持 00:00 每秒重新检测
+    fn rest_deferred_by_fullscreen(&mut self) -> bool {
+        if !self.config.suppress_during_fullscreen {
+            return false;
+        }
+        let previous = self.fullscreen_check.map(|(_, fullscreen)| fullscreen);
+        if let Some((at, fullscreen)) = self.fullscreen_check {
+            if at.elapsed() < Duration::from_secs(1) {
+                return fullscreen;
+            }
+        }
+        let fullscreen = fullscreen_app_active();
+        self.fullscreen_check = Some((Instant::now(), fullscreen));
+        match (previous, fullscreen) {
+            (Some(false) | None, true) => println!("检测到全屏程序，推迟休息"),
+            (Some(true), false) => println!("全屏程序已退出，开始休息"),
+            _ => {}
+        }
+        fullscreen
+    }
+
+    pub(crate) fn tick(&mut self) {
+        if let Some(start) = self.start_time {
+            let elapsed = self.now().saturating_duration_since(start);
+            self.add_rest_elapsed(elapsed);
+            if self.open_ended && self.state == AppState::Resting {
+                // 不限时休息只累计时长，不会自动结束
+                self.start_time = Some(self.now());
+                return;
+            }
+            if elapsed >= self.time_remaining {
+                if self.state == AppState::Working && self.rest_deferred_by_fullscreen() {
+                    self.time_remaining = Duration::ZERO;
+                    self.start_time = Some(self.now());
+                    return;
+                }
+                self.fullscreen_check = None;
+                if self.state == AppState::Working && self.session_snoozes > 0 {
+                    println!("推迟结束，开始休息");
+                    self.start_rest();
+                    return;
+                }
+                if self.state == AppState::Working {
+                    self.notify_session_completed("work", self.session_minutes);
+                    self.completed_work_sessions += 1;
+                    self.stats.record_work(today(), self.session_minutes);
+                    self.record_session(SessionKind::Work, false);
+                    if let Some(milestone) = self.stats.take_new_milestone() {
+                        println!("达成里程碑: 累计 {} 个番茄", milestone);
+                        self.pending_celebration = Some(milestone);
+                    }
+                    if self.config.celebrate_work_done {
+                        self.work_done = Some((self.now(), false));
+                    }
+                    self.stats.save();
+                    if self.config.ask_session_note {
+                        let task = self.current_task.trim();
+                        self.pending_note = Some(SessionNote {
+                            timestamp: chrono::Local::now().to_rfc3339(),
+                            duration_secs: self.session_minutes.saturating_mul(60),
+                            task: (!task.is_empty()).then(|| task.to_string()),
+                            note: String::new(),
+                        });
+                    }
+                    self.start_rest();
+                } else if self.state == AppState::Resting {
+                    self.complete_rest();
+                }
+            } else {
+                self.time_remaining -= elapsed;
+                self.start_time = Some(self.now());
+            }
+        }
+    }
+
+    // 专注中每隔几秒记一次前台程序，把这段时间算给它
+    pub(crate) fn sample_foreground_app(&mut self) {
+        const SAMPLE_SECS: u64 = 5;
+        let elapsed = self.last_app_sample.elapsed();
+        if elapsed < Duration::from_secs(SAMPLE_SECS) {
+            return;
+        }
+        self.last_app_sample = Instant::now();
+        if !self.config.track_apps || self.state != AppState::Working {
+            return;
+        }
+        if let Some(app) = foreground_app() {
+            self.stats.record_app_time(today(), app, elapsed.as_secs());
+        }
+    }
+
+    // 睡眠期间 Linux 的单调时钟不走而系统时钟会跳，Windows 上两者都会跳，取两者较大的间隔判断
+    pub(crate) fn check_clock_jump(&mut self, now: Instant, now_wall: SystemTime) {
+        let (last, last_wall) = std::mem::replace(&mut self.last_clock_check, (now, now_wall));
+        let gap = now.saturating_duration_since(last).max(now_wall.duration_since(last_wall).unwrap_or_default());
+        if gap >= SLEEP_GAP {
+            self.handle_wake_from_sleep(gap);
+        }
+    }
+
+    // 睡眠不算专注：专注中的番茄停在睡眠前的剩余时间并暂停；
+    // 休息中的话睡眠本身就是休息，直接结束这次休息
+    fn handle_wake_from_sleep(&mut self, slept: Duration) {
+        match self.state {
+            AppState::Working => {
+                println!("检测到系统睡眠约 {} 分钟，专注已暂停，剩余 {}", slept.as_secs() / 60, self.format_time());
+                self.start_time = None;
+                self.paused_from = AppState::Working;
+                self.state = AppState::Paused;
+                // 暂停从睡着时算起，唤醒后显示的"已暂停"包含整段睡眠
+                self.paused_at = Some(self.now().checked_sub(slept).unwrap_or_else(|| self.now()));
+                self.leave_rest_display();
+            }
+            AppState::Resting => {
+                println!("检测到系统睡眠约 {} 分钟，休息已结束", slept.as_secs() / 60);
+                self.reset_to_ready();
+            }
+            AppState::Paused | AppState::Ready => {
+                println!("检测到系统睡眠约 {} 分钟", slept.as_secs() / 60);
+            }
+        }
+    }
+
+    // 休息结束后回到队列里的下一个任务
+    fn advance_task_queue(&mut self) {
+        if !self.task_queue.is_empty() {
+            self.current_task = self.task_queue.remove(0);
+            println!("切换到下一个任务: {}", self.current_task);
+            self.persist_tasks();
+        }
+    }
+
+    // 当前任务和队列写回配置文件，内容没变时不写
+    pub(crate) fn persist_tasks(&mut self) {
+        if self.config.current_task == self.current_task && self.config.task_queue == self.task_queue {
+            return;
+        }
+        self.config.current_task = self.current_task.clone();
+        self.config.task_queue = self.task_queue.clone();
+        self.config.save();
+    }
+
+    // 休息最后几秒要提示的下一个任务
+    pub(crate) fn upcoming_task(&self) -> Option<&str> {
+        let ending = self.time_remaining <= Duration::from_secs(NEXT_TASK_SECS);
+        let first = self.task_queue.first().filter(|_| self.config.show_next_task && ending);
+        first.map(String::as_str)
+    }
+
+    // 休息完整结束 (倒计时走完，或不限时休息中点了"结束休息")
+    fn complete_rest(&mut self) {
+        let minutes = if self.open_ended { self.rest_elapsed.as_secs() / 60 } else { self.current_rest_minutes() };
+        self.notify_session_completed("rest", minutes);
+        self.consecutive_skips = 0;
+        self.skip_nag = false;
+        if let Some(kind) = self.current_reminder {
+            self.stats.record_reminder(today(), kind);
+            if kind == ReminderType::Water {
+                self.stats.record_water(today());
+            }
+        }
+        self.stats.record_rest(today(), self.rest_elapsed.as_secs());
+        self.record_session(SessionKind::Rest, false);
+        self.stats.save();
+        self.play_event_sound(&self.config.rest_end_sound);
+        if self.config.require_break_ack && !self.open_ended && (self.is_overlay_mode || self.is_rest_toast) {
+            // 停在 00:00 等用户点按钮；静音时段等没有显示休息提示时照常结束
+            println!("休息结束，等待确认后开始下一轮");
+            self.awaiting_ack = true;
+            self.start_time = None;
+            self.time_remaining = Duration::ZERO;
+        } else {
+            self.finish_rest();
+        }
+    }
+
+    // 休息结束后的确认: 开始下一轮，或者先回到未开始状态收起窗口
+    pub(crate) fn acknowledge_break(&mut self, start_next: bool) {
+        self.advance_task_queue();
+        self.reset_to_ready();
+        if start_next && !self.off_hours {
+            self.user_start_work();
+        } else {
+            self.should_minimize = true;
+        }
+    }
+
+    fn finish_rest(&mut self) {
+        self.advance_task_queue();
+        self.reset_to_ready();
+        match self.config.rest_end_action {
+            RestEndAction::Minimize => self.should_minimize = true,
+            RestEndAction::ShowAndWait => self.should_show_from_tray = true,
+            RestEndAction::AutoStartNext => {
+                self.should_minimize = true;
+                if !self.off_hours {
+                    println!("休息结束，自动开始下一个番茄");
+                    self.start_work();
+                }
+            }
+        }
+    }
+
+    fn notify_session_completed(&self, kind: &str, minutes: u64) {
+        let task = self.current_task.trim();
+        let command = if kind == "work" { &self.config.on_work_end_cmd } else { &self.config.on_rest_end_cmd };
+        if let Some(command) = command {
+            let duration = minutes.saturating_mul(60).to_string();
+            run_trigger_command(command, &[kind, &duration, task]);
+        }
+        let Some(url) = self.config.webhook_url.clone() else { return };
+        let payload = serde_json::json!({
+            "state": kind,
+            "duration_secs": minutes.saturating_mul(60),
+            "timestamp": chrono::Local::now().to_rfc3339(),
+            "task": if task.is_empty() { None } else { Some(task) },
+        });
+        send_webhook(url, payload.to_string());
+    }
+
+    pub(crate) fn nudging(&self) -> bool {
+        self.nudge_until.is_some_and(|until| self.now() < until)
+    }
+
+    // 暂停或一直没开始太久时定时催促一次: 托盘图标闪烁几秒、提示文字改为"别忘了开始专注"
+    pub(crate) fn check_paused_nudge(&mut self) {
+        let minutes = self.config.paused_nudge_minutes;
+        if minutes == 0 || !matches!(self.state, AppState::Paused | AppState::Ready) || self.off_hours {
+            self.paused_since = None;
+            self.nudge_until = None;
+            return;
+        }
+        if !self.nudging() {
+            self.nudge_until = None;
+        }
+        let now = self.now();
+        if self.paused_since.is_none_or(|(state, _)| state != self.state) {
+            self.paused_since = Some((self.state, now));
+        }
+        let since = self.paused_since.map_or(now, |(_, since)| since);
+        if now.saturating_duration_since(since) >= minutes_to_duration(minutes) {
+            println!("{}超过 {} 分钟，提醒开始专注", if self.state == AppState::Ready { "未开始专注" } else { "已暂停" }, minutes);
+            self.paused_since = Some((self.state, now));
+            self.nudge_until = Some(now + NUDGE_FLASH);
+        }
+    }
+
+    // 未计时 (暂停/未开始) 时空闲超时，复用隐藏到托盘的路径
+    pub(crate) fn check_idle_hide(&mut self) {
+        let limit = self.config.auto_hide_when_idle_secs;
+        if limit == 0 || !matches!(self.state, AppState::Paused | AppState::Ready) {
+            return;
+        }
+        if self.last_interaction.elapsed() >= Duration::from_secs(limit) {
+            println!("暂停状态空闲超过 {} 秒，自动隐藏到托盘", limit);
+            self.should_hide = true;
+            self.last_interaction = Instant::now();
+        }
+    }
+
+    // 休息中按配置显示已休息时长；不限时休息总是正计时
+    pub(crate) fn format_time(&self) -> String {
+        let count_up = self.config.rest_count_direction == RestCountDirection::Up || self.open_ended;
+        if self.state == AppState::Resting && count_up {
+            return format_duration(self.rest_elapsed);
+        }
+        format_duration(self.time_remaining)
+    }
+}
+
+// 测试专用的状态入口，不经过正常的开始/暂停流程
+#[cfg(test)]
+impl RestReminderApp {
+    pub(crate) fn set_state_for_test(&mut self, state: AppState, remaining: Duration) {
+        self.state = state;
+        self.time_remaining = remaining;
+        let running = matches!(state, AppState::Working | AppState::Resting);
+        self.start_time = running.then(|| self.now());
+        if running {
+            self.paused_from = state;
+        }
+    }
+
+    pub(crate) fn advance_clock(&mut self, by: Duration) {
+        self.clock_offset += by;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[test]
+    fn paused_rest_resumes_rest_and_rest_end_returns_to_ready() {
+        let mut app = RestReminderApp::from_parts(AppConfig::default(), mpsc::channel().1, None);
+        app.start_rest();
+        app.pause();
+        assert_eq!(app.state, AppState::Paused);
+        app.resume();
+        assert_eq!(app.state, AppState::Resting);
+
+        app.skip_rest();
+        assert_eq!(app.state, AppState::Ready);
+        assert_eq!(app.time_remaining, minutes_to_duration(app.config.work_minutes));
+        assert!(!app.is_overlay_mode);
+    }
+
+    #[test]
+    fn presentation_mode_shows_toast_instead_of_overlay() {
+        let (_tx, rx) = mpsc::channel();
+        let config = AppConfig { presentation_mode: true, ..Default::default() };
+        let mut app = RestReminderApp::from_parts(config, rx, None);
+
+        app.start_rest();
+        assert_eq!(app.state, AppState::Resting);
+        assert!(app.is_rest_toast);
+        assert!(!app.is_overlay_mode && !app.should_fullscreen);
+
+        app.skip_rest();
+        assert_eq!(app.state, AppState::Ready);
+        assert!(!app.is_rest_toast);
+    }
+
+    #[test]
+    fn cycle_progress_counts_toward_long_rest() {
+        let mut app = RestReminderApp::from_parts(AppConfig::default(), mpsc::channel().1, None);
+        assert_eq!(app.cycle_progress(), Some((0, 4)));
+        for _ in 0..2 {
+            app.start_work();
+            app.time_remaining = Duration::ZERO;
+            app.tick();
+            app.skip_rest();
+        }
+        assert_eq!(app.cycle_progress(), Some((2, 4)));
+        app.config.cycles_before_long_rest = 0;
+        assert_eq!(app.cycle_progress(), None);
+    }
+
+    #[test]
+    fn long_rest_after_configured_cycles() {
+        let (_tx, rx) = mpsc::channel();
+        let mut app = RestReminderApp::from_parts(AppConfig::default(), rx, None);
+        let cycles = app.config.cycles_before_long_rest;
+
+        for _ in 1..cycles {
+            app.completed_work_sessions += 1;
+            app.start_rest();
+            assert!(!app.is_long_rest);
+            assert_eq!(app.time_remaining, minutes_to_duration(app.config.rest_minutes));
+        }
+        app.completed_work_sessions += 1;
+        app.start_rest();
+        assert!(app.is_long_rest);
+        assert_eq!(app.time_remaining, minutes_to_duration(app.config.long_rest_minutes));
+        assert_eq!(app.completed_work_sessions, 0, "长休息后圆点重置");
+    }
+
+    #[test]
+    fn session_lock_pauses_and_resumes_work() {
+        let (_tx, rx) = mpsc::channel();
+        let mut app = RestReminderApp::from_parts(AppConfig::default(), rx, None);
+        app.apply_session_lock(true);
+        assert_eq!(app.state, AppState::Ready, "未在专注时锁屏不改变状态");
+        assert!(!app.paused_by_lock);
+
+        app.start_work();
+        app.time_remaining = Duration::from_secs(600);
+        app.start_time = Some(Instant::now());
+        app.apply_session_lock(true);
+        assert_eq!(app.state, AppState::Paused);
+        assert!(app.paused_by_lock);
+        let remaining = app.time_remaining;
+
+        app.apply_session_lock(false);
+        assert_eq!(app.state, AppState::Working);
+        assert_eq!(app.time_remaining, remaining, "解锁后继续剩余时间");
+        assert!(!app.paused_by_lock);
+    }
+
+    #[test]
+    fn idle_hide_only_when_paused() {
+        let (_tx, rx) = mpsc::channel();
+        let mut app = RestReminderApp::from_parts(AppConfig::default(), rx, None);
+        app.config.auto_hide_when_idle_secs = 60;
+        let long_ago = Instant::now() - Duration::from_secs(120);
+
+        app.start_work();
+        app.last_interaction = long_ago;
+        app.check_idle_hide();
+        assert!(!app.should_hide, "专注中不应自动隐藏");
+
+        app.pause();
+        app.check_idle_hide();
+        assert!(!app.should_hide, "刚进入暂停时空闲计时应重置");
+
+        app.last_interaction = long_ago;
+        app.check_idle_hide();
+        assert!(app.should_hide);
+
+        app.should_hide = false;
+        app.config.auto_hide_when_idle_secs = 0;
+        app.last_interaction = long_ago;
+        app.check_idle_hide();
+        assert!(!app.should_hide, "0 表示关闭");
+    }
+
+    #[test]
+    fn first_session_of_day_is_locked() {
+        let (_tx, rx) = mpsc::channel();
+        let config = AppConfig { lock_first_session: true, ..Default::default() };
+        let mut app = RestReminderApp::from_parts(config, rx, None);
+        app.start_work();
+        assert!(app.skip_locked());
+
+        // 完成第一个番茄后休息仍然锁定，休息结束才解锁
+        app.time_remaining = Duration::ZERO;
+        app.tick();
+        assert_eq!(app.state, AppState::Resting);
+        assert!(app.skip_locked());
+        app.time_remaining = Duration::ZERO;
+        app.tick();
+        assert_eq!(app.state, AppState::Ready);
+        assert!(!app.skip_locked());
+
+        app.start_work();
+        assert!(!app.skip_locked(), "今天已完成过番茄");
+
+        app.stats = Stats::default();
+        app.start_work();
+        app.check_first_session_rollover(today().succ_opt().unwrap());
+        assert!(!app.skip_locked(), "跨日后重新判断");
+    }
+
+    #[test]
+    fn overlay_title_expands_task() {
+        assert_eq!(expand_overlay_title(DEFAULT_OVERLAY_TITLE, "写代码"), "☕ 休息时间");
+        assert_eq!(expand_overlay_title("辛苦了: {task}", " 写代码 "), "辛苦了: 写代码");
+        assert_eq!(expand_overlay_title("{task}", ""), DEFAULT_OVERLAY_TITLE);
+    }
+
+    #[test]
+    fn fast_forward_only_while_running() {
+        let (_tx, rx) = mpsc::channel();
+        let mut app = RestReminderApp::from_parts(AppConfig::default(), rx, None);
+        app.fast_forward();
+        assert_eq!(app.time_remaining, minutes_to_duration(25), "未计时不快进");
+        app.start_work();
+        app.fast_forward();
+        assert_eq!(app.time_remaining, Duration::from_secs(2));
+    }
+
+    #[test]
+    fn quiet_hours_suppress_rest_display() {
+        let at = |h, m| chrono::NaiveDate::from_ymd_opt(2024, 1, 1).unwrap().and_hms_opt(h, m, 0).unwrap();
+        let config = AppConfig { quiet_hours_enabled: true, ..Default::default() };
+        assert!(config.is_quiet_hours(at(23, 0)));
+        assert!(config.is_quiet_hours(at(6, 59)), "跨午夜");
+        assert!(!config.is_quiet_hours(at(7, 0)));
+        assert!(!AppConfig::default().is_quiet_hours(at(23, 0)), "默认关闭");
+
+        let (_tx, rx) = mpsc::channel();
+        let mut app = RestReminderApp::from_parts(config, rx, None);
+        app.quiet_hours = true;
+        app.start_rest();
+        assert_eq!(app.state, AppState::Resting);
+        assert!(!app.is_overlay_mode && !app.should_fullscreen && !app.is_rest_toast);
+    }
+
+    #[test]
+    fn format_duration_boundaries() {
+        assert_eq!(format_duration(Duration::ZERO), "00:00");
+        assert_eq!(format_duration(Duration::from_secs(59 * 60 + 59)), "59:59");
+        assert_eq!(format_duration(Duration::from_secs(3600)), "01:00:00");
+        assert_eq!(format_duration(Duration::from_secs(2 * 3600 + 5 * 60)), "02:05:00");
+        assert_eq!(format_duration(minutes_to_duration(MAX_SESSION_MINUTES)), "24:00:00");
+        assert_eq!(format_duration(Duration::MAX), format!("{}:{:02}:{:02}", u64::MAX / 3600, u64::MAX / 60 % 60, u64::MAX % 60));
+    }
+
+    #[test]
+    fn rest_end_action_applied_when_rest_finishes() {
+        let finish = |action| {
+            let (_tx, rx) = mpsc::channel();
+            let config = AppConfig { rest_end_action: action, ..Default::default() };
+            let mut app = RestReminderApp::from_parts(config, rx, None);
+            app.start_rest();
+            app.time_remaining = Duration::ZERO;
+            app.tick();
+            app
+        };
+        let app = finish(RestEndAction::Minimize);
+        assert_eq!(app.state, AppState::Ready);
+        assert!(app.should_minimize && !app.should_show_from_tray);
+
+        let app = finish(RestEndAction::ShowAndWait);
+        assert_eq!(app.state, AppState::Ready);
+        assert!(!app.should_minimize && app.should_show_from_tray);
+
+        let app = finish(RestEndAction::AutoStartNext);
+        assert_eq!(app.state, AppState::Working);
+        assert!(app.should_minimize);
+    }
+
+    #[test]
+    fn session_note_prompt_and_recent_list() {
+        let (_tx, rx) = mpsc::channel();
+        let config = AppConfig { ask_session_note: true, ..Default::default() };
+        let mut app = RestReminderApp::from_parts(config, rx, None);
+        app.current_task = "解析器".to_string();
+        app.start_work();
+        app.time_remaining = Duration::ZERO;
+        app.tick();
+        let pending = app.pending_note.as_mut().expect("专注完成后应弹出记录");
+        assert_eq!(pending.task.as_deref(), Some("解析器"));
+        pending.note = "  重构完成 ".to_string();
+        app.finish_note(true);
+        assert!(app.pending_note.is_none());
+        assert_eq!(app.recent_notes.last().map(|n| n.note.as_str()), Some("重构完成"));
+
+        app.pending_note = Some(SessionNote { note: "不保存".to_string(), ..app.recent_notes[0].clone() });
+        app.finish_note(false);
+        assert_eq!(app.recent_notes.len(), 1, "跳过不保存");
+
+        let line = serde_json::to_string(&app.recent_notes[0]).unwrap();
+        let text = format!("{}\n坏行\n", [line.as_str(); 7].join("\n"));
+        assert_eq!(SessionNote::parse_recent(&text).len(), SessionNote::RECENT);
+    }
+
+    #[test]
+    fn smooth_overlay_fades_before_skip() {
+        let (_tx, rx) = mpsc::channel();
+        let config = AppConfig { smooth_transitions: true, ..Default::default() };
+        let mut app = RestReminderApp::from_parts(config, rx, None);
+        app.start_rest();
+        assert!(app.overlay_opacity() < 1.0, "刚开始休息时在淡入");
+
+        app.skip_rest();
+        assert_eq!(app.state, AppState::Resting, "先淡出");
+        assert!(app.overlay_fade_out.is_some());
+        app.overlay_fade_out = Some(Instant::now() - OVERLAY_FADE);
+        assert_eq!(app.overlay_opacity(), 0.0);
+        app.check_overlay_fade_out();
+        assert_eq!(app.state, AppState::Ready);
+        assert!(!app.is_overlay_mode && app.overlay_fade_out.is_none());
+
+        app.config.smooth_transitions = false;
+        app.start_rest();
+        assert_eq!(app.overlay_opacity(), 1.0);
+        app.skip_rest();
+        assert_eq!(app.state, AppState::Ready, "关闭时立即跳过");
+    }
+
+    #[test]
+    fn wake_from_three_hour_sleep() {
+        let (_tx, rx) = mpsc::channel();
+        let mut app = RestReminderApp::from_parts(AppConfig::default(), rx, None);
+        let three_hours = Duration::from_secs(3 * 3600);
+
+        app.start_work();
+        app.time_remaining = Duration::from_secs(600);
+        app.advance_clock(three_hours);
+        app.check_clock_jump(Instant::now(), SystemTime::now() + three_hours);
+        assert_eq!(app.state, AppState::Paused);
+        assert_eq!(app.paused_for().map(format_elapsed_minutes).as_deref(), Some("3 小时 0 分钟"), "已暂停从睡着时算起");
+        assert_eq!(app.time_remaining, Duration::from_secs(600), "睡眠时间不计入专注");
+        app.tick();
+        assert_eq!(app.state, AppState::Paused, "唤醒后不会立刻触发切换");
+
+        app.start_rest();
+        app.check_clock_jump(Instant::now(), SystemTime::now() + three_hours * 2);
+        assert_eq!(app.state, AppState::Ready, "过期的休息直接结束");
+        assert!(!app.is_overlay_mode);
+
+        app.start_work();
+        app.check_clock_jump(Instant::now(), SystemTime::now() + three_hours * 2 + Duration::from_secs(1));
+        assert_eq!(app.state, AppState::Working, "正常帧间隔不受影响");
+    }
+
+    #[test]
+    fn milestones_celebrated_once() {
+        let mut stats = Stats { total_completed: 98, ..Default::default() };
+        stats.record_work(today(), 25);
+        assert_eq!(stats.take_new_milestone(), None);
+        stats.record_work(today(), 25);
+        assert_eq!(stats.total_completed, 100);
+        assert_eq!(stats.take_new_milestone(), Some(100));
+        assert_eq!(stats.take_new_milestone(), None, "不重复庆祝");
+
+        let mut stats = Stats { total_completed: 1200, ..Default::default() };
+        assert_eq!(stats.take_new_milestone(), Some(1000), "跨过多个只庆祝最高的");
+        assert_eq!(stats.celebrated_milestones, vec![100, 500, 1000]);
+
+        let (_tx, rx) = mpsc::channel();
+        let mut app = RestReminderApp::from_parts(AppConfig::default(), rx, None);
+        app.stats.total_completed = 99;
+        app.start_work();
+        app.time_remaining = Duration::ZERO;
+        app.tick();
+        assert_eq!(app.pending_celebration, Some(100));
+        app.skip_rest();
+        let ctx = egui::Context::default();
+        let _ = ctx.run(Default::default(), |ctx| app.render_main(ctx));
+        assert!(app.pending_celebration.is_none());
+        assert!(!app.drops.is_empty(), "回到主界面时撒下礼花");
+    }
+
+    #[test]
+    fn focus_score_from_completions_and_skips() {
+        let day = DayStats { completed_work: 4, skipped_rests: 1, snoozes: 2, ..Default::default() };
+        assert_eq!(day.focus_score(), 4 * 10 - 5 - 2 * 2);
+        let day = DayStats { skipped_rests: 3, ..Default::default() };
+        assert_eq!(day.focus_score(), 0, "不会为负");
+
+        let (_tx, rx) = mpsc::channel();
+        let mut app = RestReminderApp::from_parts(AppConfig::default(), rx, None);
+        app.start_rest();
+        app.skip_rest();
+        app.skip_rest();
+        assert_eq!(app.stats.day(today()).map(|d| d.skipped_rests), Some(1), "只有休息中的跳过计数");
+    }
+
+    #[test]
+    fn quick_start_does_not_touch_config() {
+        let (_tx, rx) = mpsc::channel();
+        let mut app = RestReminderApp::from_parts(AppConfig::default(), rx, None);
+        app.start_quick(45);
+        assert_eq!(app.state, AppState::Working);
+        assert_eq!(app.time_remaining, minutes_to_duration(45));
+        assert_eq!(app.config.work_minutes, 25);
+        app.time_remaining = Duration::ZERO;
+        app.tick();
+        assert_eq!(app.stats.day(today()).map(|day| day.focus_secs), Some(45 * 60), "按实际时长记录");
+        app.start_work();
+        assert_eq!(app.time_remaining, minutes_to_duration(25), "下一轮恢复默认");
+    }
+
+    #[test]
+    fn minimize_to_taskbar_replaces_hide() {
+        let (_tx, rx) = mpsc::channel();
+        let mut app = RestReminderApp::from_parts(AppConfig::default(), rx, None);
+        app.should_hide = true;
+        app.route_hide_request();
+        assert!(app.should_hide && !app.should_minimize, "默认隐藏到托盘");
+        app.config.minimize_to_taskbar = true;
+        app.route_hide_request();
+        assert!(!app.should_hide && app.should_minimize);
+    }
+
+    #[test]
+    fn reminders_rotate_through_enabled_types() {
+        let (_tx, rx) = mpsc::channel();
+        let mut config = AppConfig::default();
+        assert_eq!(next_reminder(&config.reminders, 0), None, "默认全部关闭");
+        for r in &mut config.reminders {
+            r.enabled = r.kind != ReminderType::Posture;
+            if r.kind == ReminderType::Eyes {
+                r.minutes = 2;
+            }
+        }
+        let mut app = RestReminderApp::from_parts(config, rx, None);
+        let mut seen = vec![];
+        for _ in 0..4 {
+            app.start_rest();
+            seen.push(app.current_reminder.unwrap());
+        }
+        use ReminderType::*;
+        assert_eq!(seen, vec![Water, Eyes, Stretch, Water]);
+        app.start_rest();
+        assert_eq!(app.time_remaining, minutes_to_duration(2), "单独设置的时长");
+        app.time_remaining = Duration::ZERO;
+        app.tick();
+        assert_eq!(app.stats.day(today()).unwrap().reminders.get(&Eyes), Some(&1));
+        assert!(app.rest_title().contains("放松眼睛"));
+    }
+
+    #[test]
+    fn fake_clock_drives_transitions() {
+        let (_tx, rx) = mpsc::channel();
+        let mut app = RestReminderApp::from_parts(AppConfig::default(), rx, None);
+        app.set_state_for_test(AppState::Working, Duration::from_secs(60));
+        app.advance_clock(Duration::from_secs(20));
+        app.tick();
+        assert_eq!(app.state, AppState::Working);
+        assert!(app.time_remaining <= Duration::from_secs(40));
+        app.advance_clock(Duration::from_secs(40));
+        app.tick();
+        assert_eq!(app.state, AppState::Resting);
+        app.advance_clock(Duration::from_secs(30));
+        app.pause();
+        assert_eq!(app.state, AppState::Paused);
+        assert!(app.time_remaining <= minutes_to_duration(5) - Duration::from_secs(30), "暂停时按假时钟扣除");
+    }
+
+    #[test]
+    fn next_task_shows_at_end_of_rest_and_becomes_current() {
+        let (_tx, rx) = mpsc::channel();
+        let mut app = RestReminderApp::from_parts(AppConfig::default(), rx, None);
+        app.current_task = "写文档".to_string();
+        app.set_state_for_test(AppState::Resting, Duration::from_secs(60));
+        assert_eq!(app.upcoming_task(), None, "队列为空时保持原样");
+        app.task_queue = vec!["代码评审".to_string(), "回邮件".to_string()];
+        assert_eq!(app.upcoming_task(), None, "还没到最后几秒");
+        app.time_remaining = Duration::from_secs(NEXT_TASK_SECS);
+        assert_eq!(app.upcoming_task(), Some("代码评审"));
+        app.time_remaining = Duration::ZERO;
+        app.tick();
+        assert_eq!(app.current_task, "代码评审");
+        assert_eq!(app.task_queue, vec!["回邮件".to_string()]);
+    }
+
+    #[test]
+    fn end_time_in_12h_and_24h() {
+        let now = chrono::NaiveDate::from_ymd_opt(2026, 5, 4).unwrap().and_hms_opt(15, 20, 0).unwrap();
+        assert_eq!(format_end_time(now, minutes_to_duration(25), true), "15:45");
+        assert_eq!(format_end_time(now, minutes_to_duration(25), false), "3:45 PM");
+        assert_eq!(format_end_time(now, minutes_to_duration(9 * 60), true), "00:20 (明天)");
+        assert_eq!(format_end_time(now, minutes_to_duration(9 * 60), false), "12:20 AM (明天)");
+    }
+
+    #[test]
+    fn daily_cap_warns_and_forces_long_rest() {
+        let (_tx, rx) = mpsc::channel();
+        let config = AppConfig { daily_work_cap_minutes: 60, cap_forces_long_rest: true, ..Default::default() };
+        let mut app = RestReminderApp::from_parts(config, rx, None);
+        app.stats.record_work(today(), 50);
+        assert!(!app.over_daily_cap());
+        app.start_rest();
+        assert!(!app.is_long_rest);
+        app.stats.record_work(today(), 10);
+        assert!(app.over_daily_cap());
+        app.start_rest();
+        assert!(app.is_long_rest);
+        assert_eq!(app.time_remaining, minutes_to_duration(app.config.long_rest_minutes));
+        app.config.daily_work_cap_minutes = 0;
+        assert!(!app.over_daily_cap(), "0 表示关闭");
+    }
+
+    #[test]
+    fn fullscreen_defers_rest_until_it_ends() {
+        assert!(covers_monitor((0, 0, 1920, 1080), (0, 0, 1920, 1080)));
+        assert!(covers_monitor((-8, -8, 1928, 1088), (0, 0, 1920, 1080)), "最大化边框溢出也算");
+        assert!(!covers_monitor((0, 0, 1920, 1040), (0, 0, 1920, 1080)), "留出任务栏不算");
+        assert!(covers_monitor((1920, 0, 3840, 1080), (1920, 0, 3840, 1080)));
+
+        let (_tx, rx) = mpsc::channel();
+        let config = AppConfig { suppress_during_fullscreen: true, ..Default::default() };
+        let mut app = RestReminderApp::from_parts(config, rx, None);
+        app.set_state_for_test(AppState::Working, Duration::ZERO);
+        app.fullscreen_check = Some((Instant::now(), true));
+        app.tick();
+        assert_eq!(app.state, AppState::Working, "全屏时推迟");
+        assert_eq!(app.time_remaining, Duration::ZERO);
+        app.fullscreen_check = Some((Instant::now(), false));
+        app.tick();
+        assert_eq!(app.state, AppState::Resting);
+        assert_eq!(app.fullscreen_check, None);
+    }
+
+    #[test]
+    fn intensity_controls_display_and_skipping() {
+        let (_tx, rx) = mpsc::channel();
+        let config = AppConfig { intensity: Intensity::Gentle, ..Default::default() };
+        let mut app = RestReminderApp::from_parts(config, rx, None);
+        app.start_rest();
+        assert!(app.is_rest_toast && !app.is_overlay_mode, "温和模式只显示小窗");
+        assert_eq!(app.rest_lock_reason(), None);
+
+        app.config.intensity = Intensity::Strict;
+        app.config.presentation_mode = true;
+        app.start_rest();
+        assert!(app.is_overlay_mode && !app.is_rest_toast, "严格模式忽略演示模式");
+        assert!(app.rest_lock_reason().is_some());
+        app.toggle_pause();
+        assert_eq!(app.state, AppState::Resting, "严格模式不能暂停休息");
+    }
+
+    #[test]
+    fn tasks_survive_restart_through_config() {
+        let (_tx, rx) = mpsc::channel();
+        let config = AppConfig { current_task: "写周报".to_string(), task_queue: vec!["评审".to_string()], ..Default::default() };
+        let mut app = RestReminderApp::from_parts(config, rx, None);
+        assert_eq!(app.current_task, "写周报");
+        assert_eq!(app.task_queue, vec!["评审".to_string()]);
+        app.current_task = "修 bug".to_string();
+        app.start_work();
+        assert_eq!(app.config.current_task, "修 bug", "开始专注时写回配置");
+        app.advance_task_queue();
+        assert_eq!((app.config.current_task.as_str(), app.config.task_queue.len()), ("评审", 0));
+    }
+
+    #[test]
+    fn repeated_skips_make_next_rest_unskippable() {
+        let (_tx, rx) = mpsc::channel();
+        let mut app = RestReminderApp::from_parts(AppConfig::default(), rx, None);
+        for _ in 0..3 {
+            app.start_rest();
+            assert_eq!(app.rest_lock_reason(), None);
+            app.skip_rest();
+        }
+        app.start_rest();
+        assert_eq!(app.rest_lock_reason().as_deref(), Some("连续跳过了3次休息，这次请休息一下"));
+        app.time_remaining = Duration::ZERO;
+        app.tick();
+        assert_eq!(app.consecutive_skips, 0, "完整休息后清零");
+        app.start_rest();
+        assert_eq!(app.rest_lock_reason(), None);
+    }
+
+    #[test]
+    fn paused_nudge_repeats_while_paused() {
+        let (_tx, rx) = mpsc::channel();
+        let mut app = RestReminderApp::from_parts(AppConfig::default(), rx, None);
+        app.set_state_for_test(AppState::Paused, Duration::from_secs(600));
+        app.check_paused_nudge();
+        app.advance_clock(Duration::from_secs(3600));
+        app.check_paused_nudge();
+        assert!(!app.nudging(), "默认关闭");
+
+        app.config.paused_nudge_minutes = 10;
+        app.check_paused_nudge();
+        app.advance_clock(Duration::from_secs(9 * 60));
+        app.check_paused_nudge();
+        assert!(!app.nudging());
+        app.advance_clock(Duration::from_secs(60));
+        app.check_paused_nudge();
+        assert!(app.nudging());
+        assert_eq!(app.tray_tooltip_text(), "⏰ 别忘了开始专注");
+        app.advance_clock(NUDGE_FLASH);
+        app.check_paused_nudge();
+        assert!(!app.nudging(), "闪烁几秒后停止");
+        app.advance_clock(Duration::from_secs(10 * 60));
+        app.check_paused_nudge();
+        assert!(app.nudging(), "之后每隔一段时间再提醒");
+
+        app.set_state_for_test(AppState::Working, Duration::from_secs(600));
+        app.check_paused_nudge();
+        assert!(!app.nudging());
+        assert_eq!(app.paused_since, None);
+    }
+
+    #[test]
+    fn nudge_when_never_started() {
+        let config = AppConfig { paused_nudge_minutes: 10, ..Default::default() };
+        let mut app = RestReminderApp::from_parts(config, mpsc::channel().1, None);
+        app.set_state_for_test(AppState::Paused, Duration::from_secs(600));
+        app.check_paused_nudge();
+        app.advance_clock(Duration::from_secs(6 * 60));
+        app.check_paused_nudge();
+        app.set_state_for_test(AppState::Ready, Duration::from_secs(600));
+        app.check_paused_nudge();
+        app.advance_clock(Duration::from_secs(6 * 60));
+        app.check_paused_nudge();
+        assert!(!app.nudging(), "从进入未开始状态起重新计时");
+        app.advance_clock(Duration::from_secs(4 * 60));
+        app.check_paused_nudge();
+        assert!(app.nudging(), "忘了开始专注也会催促");
+        assert_eq!(app.tray_tooltip_text(), "⏰ 别忘了开始专注");
+    }
+
+    #[test]
+    fn progress_fraction_handles_zero_total() {
+        assert_eq!(progress_fraction(Duration::ZERO, Duration::ZERO), 1.0);
+        assert_eq!(progress_fraction(Duration::from_secs(5), Duration::ZERO), 1.0);
+        assert_eq!(progress_fraction(Duration::from_secs(30), Duration::from_secs(60)), 0.5);
+        assert_eq!(progress_fraction(Duration::from_secs(90), Duration::from_secs(60)), 1.0, "超出总时长时封顶");
+        assert_eq!(progress_fraction(Duration::ZERO, Duration::from_secs(60)), 0.0);
+    }
+
+    #[test]
+    fn water_breaks_count_toward_daily_cups() {
+        let (_tx, rx) = mpsc::channel();
+        let mut app = RestReminderApp::from_parts(AppConfig::default(), rx, None);
+        app.set_state_for_test(AppState::Resting, Duration::from_secs(60));
+        app.current_reminder = Some(ReminderType::Water);
+        app.advance_clock(Duration::from_secs(61));
+        app.tick();
+        app.set_state_for_test(AppState::Resting, Duration::from_secs(60));
+        app.current_reminder = Some(ReminderType::Eyes);
+        app.advance_clock(Duration::from_secs(61));
+        app.tick();
+        app.stats.record_water(today());
+        let day = app.stats.day(today()).unwrap();
+        assert_eq!(day.water_cups, 2, "只有喝水提醒和手动 +1 计数");
+        assert_eq!(app.stats.day(today().succ_opt().unwrap()).map_or(0, |day| day.water_cups), 0);
+    }
+
+    #[test]
+    fn break_ack_keeps_overlay_until_confirmed() {
+        let (_tx, rx) = mpsc::channel();
+        let config = AppConfig { require_break_ack: true, ..Default::default() };
+        let mut app = RestReminderApp::from_parts(config, rx, None);
+        app.set_state_for_test(AppState::Resting, Duration::from_secs(60));
+        app.is_overlay_mode = true;
+        app.advance_clock(Duration::from_secs(61));
+        app.tick();
+        assert!(app.awaiting_ack);
+        assert!(app.is_overlay_mode, "蒙层保持显示");
+        assert_eq!(app.time_remaining, Duration::ZERO);
+        app.advance_clock(Duration::from_secs(600));
+        app.tick();
+        assert_eq!(app.state, AppState::Resting, "不会自动开始下一轮");
+        app.acknowledge_break(true);
+        assert_eq!(app.state, AppState::Working);
+        assert!(!app.awaiting_ack && !app.is_overlay_mode);
+
+        // 没有显示休息提示时照常结束
+        app.set_state_for_test(AppState::Resting, Duration::from_secs(60));
+        app.is_overlay_mode = false;
+        app.advance_clock(Duration::from_secs(61));
+        app.tick();
+        assert!(!app.awaiting_ack);
+        assert_eq!(app.state, AppState::Ready);
+    }
+
+    #[test]
+    fn auto_minimize_fires_once_after_delay() {
+        let (_tx, rx) = mpsc::channel();
+        let config = AppConfig { auto_minimize_on_work: true, auto_minimize_delay_secs: 3, ..Default::default() };
+        let mut app = RestReminderApp::from_parts(config, rx, None);
+        app.start_work();
+        app.advance_clock(Duration::from_secs(2));
+        app.check_auto_minimize();
+        assert!(!app.should_hide);
+        app.advance_clock(Duration::from_secs(1));
+        app.check_auto_minimize();
+        assert!(app.should_hide);
+        app.should_hide = false;
+        app.check_auto_minimize();
+        assert!(!app.should_hide, "只收起一次");
+
+        app.start_work();
+        app.pause();
+        app.advance_clock(Duration::from_secs(5));
+        app.check_auto_minimize();
+        assert!(!app.should_hide, "暂停后不再收起");
+    }
+
+    #[test]
+    fn open_ended_rest_counts_up_until_ended() {
+        let (_tx, rx) = mpsc::channel();
+        let config = AppConfig { open_ended_rest: true, rest_minutes: 1, ..Default::default() };
+        let mut app = RestReminderApp::from_parts(config, rx, None);
+        app.start_rest();
+        app.advance_clock(Duration::from_secs(150));
+        app.tick();
+        assert_eq!(app.state, AppState::Resting, "不限时休息不会自动结束");
+        assert_eq!(app.format_time(), "02:30");
+        app.pause();
+        app.advance_clock(Duration::from_secs(600));
+        app.resume();
+        app.advance_clock(Duration::from_secs(30));
+        app.skip_rest();
+        assert_eq!(app.state, AppState::Ready);
+        let day = app.stats.day(today()).unwrap();
+        assert_eq!(day.rest_secs, 180, "暂停的时间不算休息");
+        assert_eq!(day.skipped_rests, 0, "手动结束不算跳过");
+    }
+
+    #[test]
+    fn open_ended_rest_keeps_locks_until_minimum() {
+        let (_tx, rx) = mpsc::channel();
+        let config = AppConfig { open_ended_rest: true, rest_minutes: 5, skip_nag_threshold: 1, ..Default::default() };
+        let mut app = RestReminderApp::from_parts(config, rx, None);
+        app.start_rest();
+        app.skip_rest();
+        assert_eq!(app.stats.day(today()).unwrap().skipped_rests, 1, "不满休息时长就结束算跳过");
+        assert_eq!(app.consecutive_skips, 1);
+
+        app.start_rest();
+        assert!(app.rest_lock_reason().is_some());
+        app.advance_clock(Duration::from_secs(60));
+        app.tick();
+        app.apply_shortcut(ShortcutAction::SkipRest);
+        assert_eq!(app.state, AppState::Resting, "连续跳过后的锁定在不限时休息中同样有效");
+        app.advance_clock(Duration::from_secs(4 * 60));
+        app.tick();
+        assert!(app.open_ended_rest_done());
+        app.apply_shortcut(ShortcutAction::SkipRest);
+        assert_eq!(app.state, AppState::Ready);
+        assert_eq!(app.consecutive_skips, 0, "休息满时长后结束算完成");
+    }
+
+    #[test]
+    fn rest_count_up_shows_elapsed() {
+        let (_tx, rx) = mpsc::channel();
+        let config = AppConfig { rest_count_direction: RestCountDirection::Up, rest_minutes: 5, ..Default::default() };
+        let mut app = RestReminderApp::from_parts(config, rx, None);
+        app.start_rest();
+        app.advance_clock(Duration::from_secs(70));
+        app.tick();
+        assert_eq!(app.format_time(), "01:10");
+        app.advance_clock(Duration::from_secs(300));
+        app.tick();
+        assert_ne!(app.state, AppState::Resting);
+        assert_eq!(app.stats.day(today()).unwrap().rest_secs, 300, "只算计划的休息时长");
+    }
+
+    #[test]
+    fn start_applies_pending_duration_input() {
+        let (_tx, rx) = mpsc::channel();
+        let mut app = RestReminderApp::from_parts(AppConfig::default(), rx, None);
+        app.work_input = "40".to_string();
+        app.rest_input = "abc".to_string();
+        app.user_start_work();
+        assert_eq!(app.session_minutes, 40);
+        assert_eq!(app.config.work_minutes, 40);
+        assert_eq!(app.rest_input, AppConfig::default().rest_minutes.to_string(), "无效输入恢复为上次的有效值");
+
+        app.rest_input = "7".to_string();
+        app.user_start_rest();
+        assert_eq!(app.time_remaining, minutes_to_duration(7));
+
+        // 自动切换时用户可能正输到一半 ("4" 还要接着输成 "45")
+        app.work_input = "4".to_string();
+        app.advance_clock(minutes_to_duration(7));
+        app.tick();
+        app.start_work();
+        app.advance_clock(minutes_to_duration(40));
+        app.tick();
+        assert_eq!(app.state, AppState::Resting);
+        assert_eq!(app.work_input, "4", "自动开始休息不提交也不覆盖输入框");
+        assert_eq!(app.config.work_minutes, 40);
+
+        app.focused_widget = Some(settings_field_id("专注时长(分):"));
+        app.rest_input = "9".to_string();
+        app.user_start_work();
+        assert_eq!(app.work_input, "4", "仍有焦点的输入框不动");
+        assert_eq!(app.config.work_minutes, 40);
+        assert_eq!(app.config.rest_minutes, 9);
+
+        app.focused_widget = None;
+        app.start_until(Duration::from_secs(20 * 60));
+        assert_eq!(app.config.work_minutes, 40, "对齐时钟不修改配置");
+    }
+
+    #[test]
+    fn snooze_is_limited_per_rest_and_recorded() {
+        let (_tx, rx) = mpsc::channel();
+        let config = AppConfig { snooze_minutes: 3, max_snoozes: 1, ..Default::default() };
+        let mut app = RestReminderApp::from_parts(config, rx, None);
+        app.start_work();
+        app.advance_clock(minutes_to_duration(app.config.work_minutes));
+        app.tick();
+        assert_eq!(app.state, AppState::Resting);
+        app.snooze_rest();
+        assert_eq!(app.state, AppState::Working);
+        assert_eq!(app.time_remaining, minutes_to_duration(3));
+
+        app.advance_clock(minutes_to_duration(3));
+        app.tick();
+        assert_eq!(app.state, AppState::Resting);
+        assert_eq!(app.stats.day(today()).unwrap().completed_work, 1, "推迟的时间不算新的番茄");
+        app.snooze_rest();
+        assert_eq!(app.state, AppState::Resting, "达到上限后不能再推迟");
+        assert_eq!(app.stats.day(today()).unwrap().snoozes, 1);
+
+        app.skip_rest();
+        assert_eq!(app.session_snoozes, 0, "休息结束后重新计数");
+    }
+
+    #[test]
+    fn snooze_after_rest_finished_is_ignored() {
+        let mut app = RestReminderApp::from_parts(AppConfig::default(), mpsc::channel().1, None);
+        app.start_rest();
+        let rest = minutes_to_duration(app.current_rest_minutes());
+        app.advance_clock(rest + Duration::from_secs(1));
+        app.snooze_rest();
+        let day = app.stats.day(today()).unwrap();
+        assert_eq!(day.snoozes, 0, "休息已经结束，不算推迟");
+        assert!(day.rest_secs.abs_diff(rest.as_secs()) <= 2, "休息时长只记一次: {}", day.rest_secs);
+        assert_ne!(app.state, AppState::Working);
+        assert_eq!(app.session_snoozes, 0);
+    }
+
+    #[test]
+    fn abandoning_snoozed_rest_counts_as_skip() {
+        let config = AppConfig { skip_cooldown_secs: 60, ..Default::default() };
+        let mut app = RestReminderApp::from_parts(config, mpsc::channel().1, None);
+        app.start_rest();
+        app.snooze_rest();
+        app.start_work();
+        assert_eq!(app.state, AppState::Ready, "跳过后的冷却中不能直接开始专注");
+        assert!(app.skip_cooldown_left().is_some());
+        assert_eq!(app.consecutive_skips, 1);
+        assert_eq!(app.stats.day(today()).unwrap().skipped_rests, 1);
+        assert!(app.stats.recent_sessions(1)[0].skipped);
+
+        app.config.skip_cooldown_secs = 0;
+        app.start_rest();
+        app.snooze_rest();
+        app.apply_shortcut(ShortcutAction::Reset);
+        assert_eq!(app.consecutive_skips, 2, "R 重置同样算跳过");
+        app.start_work();
+        app.start_work();
+        assert_eq!(app.consecutive_skips, 2, "放弃普通的专注不算跳过休息");
+    }
+
+    #[test]
+    fn hold_to_skip_also_guards_snooze() {
+        let config = AppConfig { hold_to_skip: true, ..Default::default() };
+        let mut app = RestReminderApp::from_parts(config, mpsc::channel().1, None);
+        app.start_rest();
+        app.hold_snooze(true);
+        app.advance_clock(HOLD_TO_SKIP / 2);
+        app.hold_snooze(false);
+        assert_eq!(app.state, AppState::Resting, "提前松开不推迟");
+        app.hold_snooze(true);
+        app.advance_clock(HOLD_TO_SKIP);
+        app.hold_snooze(true);
+        assert_eq!(app.state, AppState::Working);
+        assert_eq!(app.session_snoozes, 1);
+    }
+
+    #[test]
+    fn next_clock_boundary() {
+        let at = |h, m, s| chrono::NaiveDate::from_ymd_opt(2024, 5, 1).unwrap().and_hms_opt(h, m, s).unwrap();
+        assert_eq!(until_next_boundary(at(14, 10, 0), 60), Duration::from_secs(50 * 60));
+        assert_eq!(until_next_boundary(at(14, 10, 0), 30), Duration::from_secs(20 * 60));
+        assert_eq!(until_next_boundary(at(14, 45, 30), 30), Duration::from_secs(14 * 60 + 30));
+        assert_eq!(until_next_boundary(at(14, 59, 30), 60), Duration::from_secs(60 * 60 + 30), "不足一分钟顺延");
+        assert_eq!(format_end_time(at(23, 40, 0), until_next_boundary(at(23, 40, 0), 60), true), "00:00 (明天)");
+
+        let (_tx, rx) = mpsc::channel();
+        let mut app = RestReminderApp::from_parts(AppConfig::default(), rx, None);
+        app.start_until(Duration::from_secs(20 * 60));
+        assert_eq!(app.state, AppState::Working);
+        assert_eq!(app.time_remaining, Duration::from_secs(20 * 60));
+        assert_eq!(app.config.work_minutes, AppConfig::default().work_minutes, "不改配置");
+    }
+
+    #[test]
+    fn overlay_preview_leaves_timer_alone() {
+        let (_tx, rx) = mpsc::channel();
+        let mut app = RestReminderApp::from_parts(AppConfig::default(), rx, None);
+        app.start_work();
+        let remaining = app.time_remaining;
+        app.preview_overlay();
+        assert!(app.is_overlay_mode && app.should_fullscreen);
+        assert_eq!(app.state, AppState::Working);
+
+        app.advance_clock(OVERLAY_PREVIEW);
+        app.check_overlay_preview();
+        app.tick();
+        assert!(!app.is_overlay_mode && !app.should_fullscreen, "预览到时自动结束");
+        assert_eq!(app.state, AppState::Working);
+        assert!(remaining - app.time_remaining >= OVERLAY_PREVIEW, "预览期间专注照常计时");
+        assert!(app.stats.day(today()).is_none(), "预览不写统计");
+    }
+
+    #[test]
+    fn hold_to_skip_needs_a_full_hold() {
+        let (_tx, rx) = mpsc::channel();
+        let config = AppConfig { hold_to_skip: true, smooth_transitions: false, ..Default::default() };
+        let mut app = RestReminderApp::from_parts(config, rx, None);
+        app.set_state_for_test(AppState::Resting, Duration::from_secs(300));
+        app.hold_skip(true);
+        app.advance_clock(HOLD_TO_SKIP / 2);
+        assert!((app.hold_skip(true) - 0.5).abs() < 0.05);
+        assert_eq!(app.hold_skip(false), 0.0, "提前松开取消");
+        app.hold_skip(true);
+        app.advance_clock(HOLD_TO_SKIP / 2);
+        app.hold_skip(true);
+        assert_eq!(app.state, AppState::Resting, "松开后重新计时");
+        app.apply_shortcut(ShortcutAction::SkipRest);
+        assert_eq!(app.state, AppState::Resting, "Esc 不能绕过");
+        app.advance_clock(HOLD_TO_SKIP / 2);
+        app.hold_skip(true);
+        assert_eq!(app.state, AppState::Ready);
+    }
+
+    #[test]
+    fn stats_snapshot_includes_running_session() {
+        let (_tx, rx) = mpsc::channel();
+        let mut app = RestReminderApp::from_parts(AppConfig::default(), rx, None);
+        assert!(app.stats_snapshot().days.is_empty(), "未开始时没有进行中的记录");
+        app.advance_clock(Duration::from_secs(app.config.stats_autosave_secs));
+        assert!(!app.check_stats_autosave(), "启动后什么都没变，不用保存");
+        app.start_work();
+        let snapshot = app.stats_snapshot();
+        let sessions: Vec<_> = snapshot.days.values().flat_map(|day| &day.sessions).collect();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].kind, SessionKind::Work);
+        assert!(app.stats.days.is_empty(), "内存里的统计不变");
+
+        app.advance_clock(Duration::from_secs(app.config.stats_autosave_secs));
+        assert!(app.check_stats_autosave());
+        assert!(app.now().saturating_duration_since(app.last_stats_autosave) < Duration::from_secs(1), "到间隔后保存并重新计时");
+
+        let snapshot = app.stats_snapshot();
+        let day = snapshot.day(today()).unwrap();
+        assert_eq!(day.focus_secs, app.config.stats_autosave_secs, "已经专注的时间也记上");
+        assert!(day.sessions[0].in_progress, "进行中的一段不会被当成完成的");
+        assert_eq!(day.completed_work, 0);
+
+        app.pause();
+        app.advance_clock(Duration::from_secs(app.config.stats_autosave_secs));
+        assert!(app.check_stats_autosave(), "暂停后再存一次暂停时的进度");
+        app.advance_clock(Duration::from_secs(app.config.stats_autosave_secs));
+        assert!(!app.check_stats_autosave(), "没有变化时不再保存");
+    }
+}
@@ -0,0 +1,426 @@
+//! 托盘图标、菜单和悬停提示
+
+use eframe::egui;
+use std::sync::mpsc::Sender;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use tray_icon::menu::{CheckMenuItem, Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu};
+use tray_icon::{TrayIcon, TrayIconBuilder, TrayIconEvent};
+
+use crate::*;
+
+pub(crate) const NUDGE_FLASH: Duration = Duration::from_secs(6); // 暂停催促时托盘图标闪烁多久
+
+pub(crate) const TRAY_TOOLTIP: &str = "番茄钟助手 - 点击显示窗口";
+pub(crate) const DEFAULT_TOOLTIP_TEMPLATE: &str = "{state} {time}";
+// 托盘线程的轮询间隔；窗口隐藏且没有在计时时放慢，但点击延迟仍控制在 100ms 以内
+pub(crate) const TRAY_POLL_ACTIVE: Duration = Duration::from_millis(50);
+pub(crate) const TRAY_POLL_IDLE: Duration = Duration::from_millis(90);
+
+pub(crate) static TRAY_SHOW_REQUEST: AtomicBool = AtomicBool::new(false);
+pub(crate) static TRAY_QUIT_REQUEST: AtomicBool = AtomicBool::new(false);
+
+// 左键点击是否会显示窗口 (Show/Toggle)：窗口隐藏时主循环可能不刷新，托盘线程需直接显示
+pub(crate) static TRAY_CLICK_SHOWS: AtomicBool = AtomicBool::new(true);
+// 是否正在计时 (专注/休息)，由主循环每帧写入，托盘线程据此调整轮询间隔
+pub(crate) static TIMER_RUNNING: AtomicBool = AtomicBool::new(false);
+
+// 显示/退出菜单仍由后台线程直接处理，切换预设和图标左键点击经消息通道交给主循环
+#[derive(Debug, Clone)]
+pub(crate) enum TrayMessage {
+    MenuClick(String), // 菜单被点击 (show/quit/preset:<序号>)
+    IconClick,         // 托盘图标本身被点击 (左键)
+}
+
+// 不认识的占位符原样保留；模板为空时用默认格式
+pub(crate) fn expand_tooltip_template(template: &str, state: &str, time: &str, task: &str, today_count: u32) -> String {
+    let template = if template.trim().is_empty() { DEFAULT_TOOLTIP_TEMPLATE } else { template };
+    template
+        .replace("{state}", state)
+        .replace("{time}", time)
+        .replace("{task}", task.trim())
+        .replace("{today_count}", &today_count.to_string())
+        .trim()
+        .to_string()
+}
+
+// 创建一个更明显的托盘图标 - 指定颜色的圆形
+pub(crate) fn tray_icon_rgba(color: [u8; 3]) -> Vec<u8> {
+    let mut icon_data = vec![0; 64 * 64 * 4]; // 64x64 RGBA
+    for y in 0..64 {
+        for x in 0..64 {
+            let idx = (y * 64 + x) * 4;
+            let center_x = 32;
+            let center_y = 32;
+            let distance = ((x as i32 - center_x).pow(2) + (y as i32 - center_y).pow(2)) as f32;
+
+            if distance <= 25.0 * 25.0 {
+                icon_data[idx..idx + 3].copy_from_slice(&color);
+                icon_data[idx + 3] = 255; // A
+            } else {
+                // 透明背景
+                icon_data[idx + 3] = 0;   // A
+            }
+        }
+    }
+    icon_data
+}
+
+pub(crate) fn init_tray(sender: Sender<TrayMessage>, ctx: egui::Context) -> Result<(TrayIcon, Menu, Submenu, MenuItem), Box<dyn std::error::Error>> {
+    // 启动时先用番茄红，第一帧后按状态颜色更新
+    let icon = tray_icon::Icon::from_rgba(tray_icon_rgba([255, 99, 71]), 64, 64)?;
+
+    let menu = Menu::new();
+    // 预设列表由主线程按配置填充 (rebuild_tray_presets)
+    let presets = Submenu::with_id("presets", "预设", true);
+    let status = MenuItem::with_id("status", format!("状态: {}", AppState::Ready.label()), false, None);
+    menu.append(&status)?;
+    menu.append(&PredefinedMenuItem::separator())?;
+    menu.append(&MenuItem::with_id("show", "显示窗口", true, None))?;
+    menu.append(&presets)?;
+    menu.append(&MenuItem::with_id("quit", "退出程序", true, None))?;
+
+    let tray = TrayIconBuilder::new()
+        .with_menu(Box::new(menu.clone()))
+        .with_tooltip(TRAY_TOOLTIP)
+        .with_icon(icon)
+        .build()?;
+
+    // 启动托盘事件监听线程 (使用原子变量而不是消息通道)
+    std::thread::spawn(move || {
+        let menu_channel = MenuEvent::receiver();
+        let tray_channel = TrayIconEvent::receiver();
+
+        println!("托盘监听线程已启动...");
+
+        loop {
+            let mut event_handled = false;
+
+            // 检查菜单点击事件
+            if let Ok(event) = menu_channel.try_recv() {
+                let id = event.id().0.clone();
+                println!("后台线程捕获菜单事件: {}", id);
+
+                match id.as_str() {
+                    "show" => {
+                        println!("直接处理显示窗口请求");
+                        show_window_directly();
+                        event_handled = true;
+                    }
+                    "quit" => {
+                        println!("直接退出应用程序");
+                        std::process::exit(0);
+                    }
+                    id if id.starts_with("preset:") => {
+                        let _ = sender.send(TrayMessage::MenuClick(id.to_string()));
+                        event_handled = true;
+                    }
+                    _ => {}
+                }
+            }
+
+            // 检查托盘图标点击事件 (只处理左键点击，右键让系统显示菜单)
+            if let Ok(event) = tray_channel.try_recv() {
+                match event {
+                    TrayIconEvent::Click { button, .. } => {
+                        if button == tray_icon::MouseButton::Left {
+                            println!("后台线程捕获图标左键点击事件");
+                            let hidden = WINDOW_HIDDEN.load(Ordering::SeqCst);
+                            if cfg!(target_os = "windows") && hidden && TRAY_CLICK_SHOWS.load(Ordering::SeqCst) {
+                                // 隐藏时主循环不一定在跑，直接显示；显示后就不再交给主循环切换
+                                show_window_directly();
+                            } else {
+                                let _ = sender.send(TrayMessage::IconClick);
+                            }
+                            event_handled = true;
+                        } else {
+                            println!("右键点击，让系统显示菜单");
+                        }
+                    }
+                    TrayIconEvent::DoubleClick { button: tray_icon::MouseButton::Left, .. } => {
+                        println!("后台线程捕获图标左键双击事件，直接处理显示窗口请求");
+                        show_window_directly();
+                        event_handled = true;
+                    }
+                    _ => {}
+                }
+            }
+
+            // 如果处理了事件，触发重绘
+            if event_handled {
+                ctx.request_repaint();
+            }
+
+            std::thread::sleep(tray_poll_interval(
+                WINDOW_HIDDEN.load(Ordering::SeqCst),
+                TIMER_RUNNING.load(Ordering::SeqCst),
+            ));
+        }
+    });
+
+    Ok((tray, menu, presets, status))
+}
+
+pub(crate) fn tray_poll_interval(hidden: bool, timer_running: bool) -> Duration {
+    if hidden && !timer_running { TRAY_POLL_IDLE } else { TRAY_POLL_ACTIVE }
+}
+
+impl RestReminderApp {
+    // 退出优先: 同一帧里也有显示请求 (包括菜单消息触发的) 时直接丢掉，
+    // 否则会先走完唤醒窗口的流程，下一帧才退出，退出前窗口闪一下
+    pub(crate) fn apply_tray_flags(&mut self, show: bool, quit: bool) -> usize {
+        if show {
+            println!("主界面检测到显示窗口请求");
+            self.should_show_from_tray = true;
+        }
+        if quit {
+            println!("主界面检测到退出请求");
+            self.should_quit = true;
+        }
+        if self.should_quit && self.should_show_from_tray {
+            println!("正在退出，跳过显示窗口");
+            self.should_show_from_tray = false;
+        }
+        usize::from(show) + usize::from(quit)
+    }
+
+    // 不喜欢托盘的用户: 所有隐藏请求 (关闭、空闲、启动隐藏) 改为普通最小化
+    pub(crate) fn route_hide_request(&mut self) {
+        if self.should_hide && self.config.minimize_to_taskbar {
+            self.should_hide = false;
+            self.should_minimize = true;
+        }
+    }
+
+    // 例如 "专注中 23:10 · 今日 4 🍅"
+    pub(crate) fn tray_tooltip_text(&self) -> String {
+        let base = match self.state {
+            _ if self.quiet_hours => "🌙 番茄钟助手 - 静音时段".to_string(),
+            AppState::Paused | AppState::Ready if self.nudging() => "⏰ 别忘了开始专注".to_string(),
+            AppState::Ready => TRAY_TOOLTIP.to_string(),
+            AppState::Working | AppState::Resting | AppState::Paused => expand_tooltip_template(
+                &self.config.tooltip_template,
+                self.state.label(),
+                &self.format_time(),
+                &self.current_task,
+                self.stats.completed_on(today()),
+            ),
+        };
+        if !self.config.tooltip_show_stats {
+            return base;
+        }
+        format!("{} · 今日 {} 🍅", base, self.stats.completed_on(today()))
+    }
+
+    // 每帧调用；内容变化且距上次更新超过 1 秒才真正设置，减少系统调用
+    pub(crate) fn update_tray_tooltip(&mut self) {
+        let (last, at) = &self.tray_tooltip;
+        if at.elapsed() < Duration::from_secs(1) {
+            return;
+        }
+        let tooltip = self.tray_tooltip_text();
+        if tooltip == *last {
+            return;
+        }
+        self.tray_tooltip = (tooltip, Instant::now());
+        let Some(tray) = &self._tray_icon else { return };
+        if let Err(e) = tray.set_tooltip(Some(&self.tray_tooltip.0)) {
+            println!("更新托盘提示失败: {}", e);
+        }
+    }
+
+    // 托盘图标跟随状态变色
+    pub(crate) fn update_tray_icon(&mut self) {
+        let mut color = self.config.accent_colors.for_state(self.state);
+        let blink = self.nudge_until.map(|until| until.saturating_duration_since(self.now()).as_millis() / 500 % 2 == 0);
+        if blink == Some(true) {
+            color = self.config.accent_colors.working;
+        }
+        if self.tray_icon_color == Some(color) {
+            return;
+        }
+        self.tray_icon_color = Some(color);
+        let Some(tray) = &self._tray_icon else { return };
+        let result = tray_icon::Icon::from_rgba(tray_icon_rgba(color), 64, 64)
+            .map_err(|e| e.to_string())
+            .and_then(|icon| tray.set_icon(Some(icon)).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            println!("更新托盘图标失败: {}", e);
+        }
+    }
+
+    // 预设增删或当前预设变化后重建托盘子菜单
+    pub(crate) fn rebuild_tray_presets(&mut self) {
+        let Some(submenu) = &self.tray_presets else { return };
+        for item in self.tray_preset_items.drain(..) {
+            let _ = submenu.remove(&item);
+        }
+        let active = self.config.active_preset();
+        for (i, preset) in self.config.presets.iter().enumerate() {
+            let item = CheckMenuItem::with_id(format!("preset:{}", i), preset.label(), true, active == Some(i), None);
+            if let Err(e) = submenu.append(&item) {
+                println!("添加预设菜单失败: {}", e);
+            }
+            self.tray_preset_items.push(item);
+        }
+    }
+
+    // 只改菜单项文字，不重建菜单，不会产生新的菜单句柄
+    pub(crate) fn update_tray_status(&mut self) {
+        if self.tray_status_state == Some(self.state) {
+            return;
+        }
+        self.tray_status_state = Some(self.state);
+        if let Some(item) = &self.tray_status {
+            item.set_text(format!("状态: {}", self.state.label()));
+        }
+    }
+
+    pub(crate) fn process_tray_message(&mut self, msg: TrayMessage) {
+        match msg {
+            TrayMessage::MenuClick(id) => {
+                match id.as_str() {
+                    "show" => {
+                        println!("处理显示窗口请求");
+                        self.should_show_from_tray = true;
+                    }
+                    "quit" => {
+                        println!("处理退出请求");
+                        self.should_quit = true;
+                    }
+                    id if id.starts_with("preset:") => {
+                        match id["preset:".len()..].parse() {
+                            Ok(index) => self.apply_preset(index),
+                            Err(_) => println!("无效的预设菜单ID: {}", id),
+                        }
+                    }
+                    _ => {
+                        println!("未知菜单ID: {}", id);
+                    }
+                }
+            }
+            TrayMessage::IconClick => {
+                println!("处理托盘图标点击: {}", self.config.tray_left_click_action.label());
+                match self.config.tray_left_click_action {
+                    TrayClickAction::Show => self.should_show_from_tray = true,
+                    TrayClickAction::Toggle if WINDOW_HIDDEN.load(Ordering::SeqCst) => self.should_show_from_tray = true,
+                    TrayClickAction::Toggle => self.should_hide = true,
+                    TrayClickAction::PauseResume => self.toggle_pause(),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[test]
+    fn tray_click_action_toggle_and_pause() {
+        let (tx, rx) = mpsc::channel();
+        let config = AppConfig { tray_left_click_action: TrayClickAction::PauseResume, ..Default::default() };
+        let mut app = RestReminderApp::from_parts(config, rx, None);
+        tx.send(TrayMessage::IconClick).unwrap();
+        app.process_tray_message(app.tray_receiver.try_recv().unwrap());
+        assert_eq!(app.state, AppState::Working);
+        app.process_tray_message(TrayMessage::IconClick);
+        assert_eq!(app.state, AppState::Paused);
+        app.process_tray_message(TrayMessage::IconClick);
+        assert_eq!(app.state, AppState::Working);
+
+        app.config.tray_left_click_action = TrayClickAction::Toggle;
+        app.process_tray_message(TrayMessage::IconClick);
+        assert!(app.should_hide, "窗口可见时点击隐藏");
+    }
+
+    #[test]
+    fn tray_preset_click_applies_without_starting() {
+        let (tx, rx) = mpsc::channel();
+        let mut app = RestReminderApp::from_parts(AppConfig::default(), rx, None);
+        assert_eq!(app.config.active_preset(), Some(0));
+        tx.send(TrayMessage::MenuClick("preset:1".to_string())).unwrap();
+        app.process_tray_message(app.tray_receiver.try_recv().unwrap());
+        assert_eq!(app.state, AppState::Ready, "切换预设不开始计时");
+        assert_eq!((app.config.work_minutes, app.config.rest_minutes), (50, 10));
+        assert_eq!(app.config.active_preset(), Some(1));
+        assert_eq!(app.format_time(), "50:00");
+        assert_eq!(app.work_input, "50");
+
+        app.process_tray_message(TrayMessage::MenuClick("preset:99".to_string()));
+        app.process_tray_message(TrayMessage::MenuClick("preset:x".to_string()));
+        assert_eq!(app.config.work_minutes, 50, "无效预设忽略");
+    }
+
+    #[test]
+    fn tray_tooltip_includes_countdown_and_stats() {
+        let (_tx, rx) = mpsc::channel();
+        let mut app = RestReminderApp::from_parts(AppConfig::default(), rx, None);
+        assert_eq!(app.tray_tooltip_text(), TRAY_TOOLTIP);
+        app.set_state_for_test(AppState::Working, Duration::from_secs(23 * 60 + 10));
+        assert_eq!(app.tray_tooltip_text(), "专注中 23:10");
+        app.config.tooltip_show_stats = true;
+        for _ in 0..4 {
+            app.stats.record_work(today(), 25);
+        }
+        assert_eq!(app.tray_tooltip_text(), "专注中 23:10 · 今日 4 🍅");
+
+        app.tray_tooltip.1 = Instant::now();
+        app.update_tray_tooltip();
+        assert_eq!(app.tray_tooltip.0, TRAY_TOOLTIP, "1 秒内不更新");
+        app.tray_tooltip.1 = Instant::now() - Duration::from_secs(1);
+        app.update_tray_tooltip();
+        assert_eq!(app.tray_tooltip.0, "专注中 23:10 · 今日 4 🍅");
+    }
+
+    #[test]
+    fn state_labels_for_tray_status() {
+        let (_tx, rx) = mpsc::channel();
+        let mut app = RestReminderApp::from_parts(AppConfig::default(), rx, None);
+        app.update_tray_status();
+        assert_eq!(app.tray_status_state, Some(AppState::Ready));
+        app.start_work();
+        app.update_tray_status();
+        assert_eq!(app.tray_status_state, Some(AppState::Working));
+        assert_eq!(AppState::Working.label(), "专注中");
+    }
+
+    #[test]
+    fn quit_wins_over_show_in_same_frame() {
+        let (_tx, rx) = mpsc::channel();
+        let mut app = RestReminderApp::from_parts(AppConfig::default(), rx, None);
+        assert_eq!(app.apply_tray_flags(true, false), 1);
+        assert!(app.should_show_from_tray);
+        app.should_show_from_tray = false;
+        assert_eq!(app.apply_tray_flags(true, true), 2);
+        assert!(app.should_quit);
+        assert!(!app.should_show_from_tray, "退出时不再唤醒窗口");
+    }
+
+    #[test]
+    fn tray_polls_slower_only_when_hidden_and_idle() {
+        assert_eq!(tray_poll_interval(true, false), TRAY_POLL_IDLE);
+        assert_eq!(tray_poll_interval(true, true), TRAY_POLL_ACTIVE);
+        assert_eq!(tray_poll_interval(false, false), TRAY_POLL_ACTIVE);
+        assert!(TRAY_POLL_IDLE < Duration::from_millis(100), "点击延迟要低于 100ms");
+    }
+
+    #[test]
+    fn tooltip_template_placeholders() {
+        assert_eq!(expand_tooltip_template(DEFAULT_TOOLTIP_TEMPLATE, "专注中", "23:10", "", 0), "专注中 23:10");
+        assert_eq!(
+            expand_tooltip_template("{time} | {task} | 🍅{today_count}", "专注中", "05:00", " 写周报 ", 3),
+            "05:00 | 写周报 | 🍅3"
+        );
+        assert_eq!(expand_tooltip_template("{state} {unknown}", "已暂停", "", "", 0), "已暂停 {unknown}", "不认识的占位符原样保留");
+        assert_eq!(expand_tooltip_template("  ", "休息中", "01:00", "", 0), "休息中 01:00");
+
+        let (_tx, rx) = mpsc::channel();
+        let config = AppConfig { tooltip_template: "{task} 还剩 {time}".to_string(), ..Default::default() };
+        let mut app = RestReminderApp::from_parts(config, rx, None);
+        app.current_task = "写周报".to_string();
+        app.set_state_for_test(AppState::Working, Duration::from_secs(90));
+        assert_eq!(app.tray_tooltip_text(), "写周报 还剩 01:30");
+    }
+}
@@ -0,0 +1,2763 @@
+//! egui 界面: 主窗口、休息蒙层、设置面板、字体和主题
+
+use eframe::egui;
+use std::sync::mpsc;
+use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant, SystemTime};
+
+#[cfg(target_os = "windows")]
+use raw_window_handle::{HasWindowHandle, RawWindowHandle};
+#[cfg(target_os = "windows")]
+use winapi::shared::windef::HWND;
+#[cfg(target_os = "windows")]
+use winapi::um::winuser::{SetForegroundWindow, ShowWindow, SW_RESTORE};
+
+use crate::*;
+
+pub(crate) const APP_VERSION: &str = env!("CARGO_PKG_VERSION");
+// 各版本的更新内容，新版本加在最前面
+pub(crate) const CHANGELOG: &[(&str, &str)] = &[
+    ("0.1.0", "• 番茄钟专注/休息自动切换\n• 全屏休息蒙层与表情动画\n• 托盘常驻、开机自启\n• 长休息、工作时间表、演示模式等设置"),
+];
+
+pub(crate) const NEXT_TASK_SECS: u64 = 30; // 休息最后多少秒在蒙层上提示下一个任务
+pub(crate) const WORK_CHIPS: [u64; 4] = [15, 25, 45, 90]; // 设置里的常用专注时长 (分)
+pub(crate) const REST_CHIPS: [u64; 3] = [5, 10, 15];
+pub(crate) const WORK_DONE_TOAST: Duration = Duration::from_secs(4); // 专注完成的祝贺显示多久
+
+pub(crate) const MAX_DROPS: usize = 200;              // 同屏表情数量上限，生成概率再高也不超过
+pub(crate) const EMOJI_SPEED_BOUNDS: std::ops::RangeInclusive<f32> = 10.0..=1000.0;
+pub(crate) const WINDOW_SIZE: [f32; 2] = [400.0, 550.0];
+pub(crate) const MIN_WINDOW_SIZE: [f32; 2] = [300.0, 400.0];
+pub(crate) const MAIN_CONTENT_WIDTH: f32 = 560.0; // 主界面内容的最大宽度，窗口放大后居中显示，不拉伸按钮
+pub(crate) const TOAST_SIZE: [f32; 2] = [260.0, 100.0];
+pub(crate) const REST_WINDOW_SIZE: [f32; 2] = [640.0, 520.0]; // 窗口模式休息时的窗口大小
+
+// 启动时无法继续运行的错误，run() 里弹窗后退出
+#[derive(Debug)]
+pub(crate) enum AppError {
+    Tray(String),
+}
+
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::Tray(e) => write!(f, "无法创建托盘图标: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+pub(crate) struct EmojiDrop {
+    emoji: String,
+    x: f32,
+    y: f32,
+    speed: f32,
+}
+
+// 主界面最近几段的记录点个数
+pub(crate) const RECENT_SESSION_DOTS: usize = 10;
+
+pub(crate) const BREATHING_IN_SECS: f32 = 4.0;
+pub(crate) const BREATHING_HOLD_SECS: f32 = 2.0;
+pub(crate) const BREATHING_OUT_SECS: f32 = 4.0;
+pub(crate) const BREATHING_CYCLE_SECS: f32 = BREATHING_IN_SECS + BREATHING_HOLD_SECS + BREATHING_OUT_SECS;
+
+// 周期内的秒数 -> (圆圈大小 0..=1, 提示文字)，吸气和呼气用缓动让变化更柔和
+pub(crate) fn breathing_phase(secs: f32) -> (f32, &'static str) {
+    let ease = |t: f32| (1.0 - (t.clamp(0.0, 1.0) * std::f32::consts::PI).cos()) / 2.0;
+    if secs < BREATHING_IN_SECS {
+        (ease(secs / BREATHING_IN_SECS), "吸气")
+    } else if secs < BREATHING_IN_SECS + BREATHING_HOLD_SECS {
+        (1.0, "屏息")
+    } else {
+        let t = (secs - BREATHING_IN_SECS - BREATHING_HOLD_SECS) / BREATHING_OUT_SECS;
+        (1.0 - ease(t), "呼气")
+    }
+}
+
+// 声音诊断里的一行结论: 有没有设备、选中的设备还在不在
+pub(crate) fn audio_device_status(devices: &Result<Vec<String>, String>, selected: &Option<String>) -> String {
+    match (devices, selected) {
+        (Err(e), _) => format!("❌ 未找到音频输出设备: {}", e),
+        (Ok(names), _) if names.is_empty() => "❌ 未找到音频输出设备，请检查声卡/耳机连接".to_string(),
+        (Ok(names), Some(name)) if !names.contains(name) => format!("⚠ 已选设备 {} 不可用，将使用系统默认", name),
+        (Ok(names), Some(name)) => format!("✔ 找到 {} 个输出设备，当前使用 {}", names.len(), name),
+        (Ok(names), None) => format!("✔ 找到 {} 个输出设备，当前使用系统默认", names.len()),
+    }
+}
+
+// 窗口内的键盘快捷键 (输入框有焦点时不响应)，设置里的"快捷键"列表也从这里读取
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) enum ShortcutAction {
+    TogglePause,
+    Reset,
+    SkipRest,
+}
+
+impl ShortcutAction {
+    fn label(&self) -> &'static str {
+        match self {
+            ShortcutAction::TogglePause => "开始 / 暂停 / 继续",
+            ShortcutAction::Reset => "重置本轮",
+            ShortcutAction::SkipRest => "跳过休息",
+        }
+    }
+}
+
+pub(crate) const SHORTCUTS: [(egui::Key, ShortcutAction); 3] = [
+    (egui::Key::Space, ShortcutAction::TogglePause),
+    (egui::Key::R, ShortcutAction::Reset),
+    (egui::Key::Escape, ShortcutAction::SkipRest),
+];
+
+// 蒙层实际采用的窗口形式，进入蒙层时按配置决定，退出时按同样的形式恢复
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) enum OverlayStyle {
+    Fullscreen,
+    Borderless,
+    Windowed,
+}
+
+impl RestReminderApp {
+    // 托盘创建失败是致命错误 (隐藏后无法唤醒)，由 run() 弹窗提示；
+    // 字体、配置、统计读取失败只记下来，在主界面顶部显示
+    pub(crate) fn new(cc: &eframe::CreationContext<'_>) -> Result<Self, AppError> {
+        attach_console(); // 开启控制台
+        let mut warnings = vec![];
+        setup_fonts(&cc.egui_ctx, &mut warnings); // 设置字体
+
+        watch_session_lock(cc.egui_ctx.clone());
+
+        let (tx, rx) = mpsc::channel();
+        
+        // 创建托盘
+        let tray = init_tray(tx, cc.egui_ctx.clone()).map_err(|e| AppError::Tray(e.to_string()))?;
+
+        if let Err(e) = check_config_dir(AppConfig::resolved_config_path().as_deref()) {
+            println!("{}，本次运行不保存任何数据", e);
+            warnings.push(format!("{}。本次运行的设置和统计只保存在内存中，退出后不会保留", e));
+            MEMORY_ONLY.store(true, Ordering::Relaxed);
+        }
+        let mut config = AppConfig::load(&mut warnings);
+        if config.last_seen_version.is_empty() {
+            // 全新安装不弹更新内容
+            config.last_seen_version = APP_VERSION.to_string();
+            config.save();
+        }
+        apply_theme(&cc.egui_ctx, config.high_contrast);
+        STEAL_FOCUS_ON_SHOW.store(config.steal_focus_on_show, Ordering::SeqCst);
+        TRAY_CLICK_SHOWS.store(config.tray_left_click_action != TrayClickAction::PauseResume, Ordering::SeqCst);
+
+        let mut app = Self::from_parts(config, rx, Some(tray));
+        app.rebuild_tray_presets();
+        app.stats = Stats::load(&mut warnings);
+        app.session_log = SessionLog::open(&mut warnings);
+        app.startup_warnings = warnings;
+        app.recent_notes = SessionNote::load_recent();
+        if cfg!(target_os = "linux") {
+            println!("窗口唤醒方式: {}", if app.wayland { "Wayland (只请求用户注意)" } else { "X11 (抢占焦点)" });
+        }
+        if app.config.start_hidden {
+            // 第一帧再隐藏，保证窗口句柄已经保存，托盘可以唤醒
+            println!("启动时隐藏到托盘");
+            app.should_hide = true;
+        }
+        if app.config.start_working_on_launch {
+            println!("启动时自动开始专注");
+            app.start_work();
+        }
+        Ok(app)
+    }
+
+    // 没有在计时、窗口藏在托盘、也没有动画时可以完全不重绘；
+    // 托盘线程处理事件后会调用 ctx.request_repaint() 唤醒界面
+    fn can_stop_repainting(&self, hidden: bool) -> bool {
+        self.config.idle_repaint_pause
+            && hidden
+            && matches!(self.state, AppState::Paused | AppState::Ready)
+            && self.drops.is_empty()
+            && !self.should_show_from_tray
+    }
+
+    // 开启前先说明会写哪个注册表项，确认后才写；关闭不需要确认
+    fn request_auto_start(&mut self, enable: bool) {
+        if enable && !self.config.auto_start_confirmed {
+            self.confirm_auto_start = Some(false);
+        } else {
+            self.set_auto_start(enable);
+        }
+    }
+
+    fn render_auto_start_confirm(&mut self, ctx: &egui::Context) {
+        let Some(mut dont_ask) = self.confirm_auto_start else { return };
+        let mut result = None;
+        egui::Window::new("开启开机自启")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label("开机自启会在当前用户的注册表中写入一项:");
+                ui.monospace(format!(r"HKEY_CURRENT_USER\{}", AUTO_START_RUN_KEY));
+                ui.label(format!("值名 \"{}\"，内容为本程序的路径。关闭开机自启时会删除这一项。", AUTO_START_VALUE));
+                ui.checkbox(&mut dont_ask, "不再提示");
+                ui.horizontal(|ui| {
+                    if ui.button("确定开启").clicked() {
+                        result = Some(true);
+                    }
+                    if ui.button("取消").clicked() {
+                        result = Some(false);
+                    }
+                });
+            });
+        self.confirm_auto_start = Some(dont_ask);
+        match result {
+            Some(true) => {
+                self.confirm_auto_start = None;
+                if dont_ask {
+                    self.config.auto_start_confirmed = true;
+                    self.config.save();
+                }
+                self.set_auto_start(true);
+            }
+            Some(false) => self.confirm_auto_start = None,
+            None => {}
+        }
+    }
+
+    // 以注册表的实际状态为准，写入失败时复选框保持原状并提示
+    fn set_auto_start(&mut self, enable: bool) {
+        let result = toggle_auto_start(enable);
+        self.auto_start_enabled = check_auto_start();
+        if let Err(e) = result {
+            println!("设置开机自启失败: {}", e);
+            self.show_error(format!("开机自启设置失败: {}", e));
+        } else if self.auto_start_enabled != enable && cfg!(target_os = "windows") && !cfg!(test) {
+            self.show_error("开机自启设置未生效，请检查权限".to_string());
+        }
+    }
+
+    fn show_error(&mut self, message: String) {
+        self.error_toast = Some((message, Instant::now()));
+    }
+
+    fn export_settings(&mut self) {
+        let Some(path) = pick_settings_file(true) else { return };
+        let result = self.config
+            .to_text(ConfigFormat::for_path(&path))
+            .and_then(|text| write_atomic(&path, &text).map_err(|e| e.to_string()));
+        match result {
+            Ok(()) => println!("设置已导出到 {}", path.display()),
+            Err(e) => self.show_error(format!("导出设置失败: {}", e)),
+        }
+    }
+
+    fn import_settings(&mut self) {
+        let Some(path) = pick_settings_file(false) else { return };
+        let format = ConfigFormat::for_path(&path);
+        let result = std::fs::read_to_string(&path).map_err(|e| e.to_string()).and_then(|text| AppConfig::parse_as(&text, format));
+        match result {
+            Ok(config) => {
+                println!("已从 {} 导入设置", path.display());
+                self.apply_imported_config(config);
+            }
+            Err(e) => self.show_error(format!("导入设置失败: {}", e)),
+        }
+    }
+
+    // 任务、声音文件夹、看过的版本属于本机，保留原值；其余设置整体替换并同步输入框
+    fn apply_imported_config(&mut self, mut config: AppConfig) {
+        config.current_task = std::mem::take(&mut self.config.current_task);
+        config.task_queue = std::mem::take(&mut self.config.task_queue);
+        config.sounds_dir = std::mem::take(&mut self.config.sounds_dir);
+        config.last_seen_version = std::mem::take(&mut self.config.last_seen_version);
+        self.config = config;
+        self.config.save();
+        self.sync_inputs_from_config();
+        let config = &self.config;
+        STEAL_FOCUS_ON_SHOW.store(config.steal_focus_on_show, Ordering::SeqCst);
+        TRAY_CLICK_SHOWS.store(config.tray_left_click_action != TrayClickAction::PauseResume, Ordering::SeqCst);
+        if self.state == AppState::Ready {
+            self.time_remaining = minutes_to_duration(self.config.work_minutes);
+        }
+        self.tray_icon_color = None;
+        self.rebuild_tray_presets();
+    }
+
+    // 配置在输入框之外被改动 (导入、切换预设) 后刷新设置里的输入框；
+    // 用户正在编辑的那个不动，免得打到一半被覆盖
+    pub(crate) fn sync_inputs_from_config(&mut self) {
+        let config = &self.config;
+        let fields = [
+            ("专注时长(分):", &mut self.work_input, config.work_minutes.to_string()),
+            ("休息时长(分):", &mut self.rest_input, config.rest_minutes.to_string()),
+            ("长休息时长(分):", &mut self.long_rest_input, config.long_rest_minutes.to_string()),
+            ("每几个番茄长休息(0=关闭):", &mut self.cycles_input, config.cycles_before_long_rest.to_string()),
+            ("空闲自动隐藏(分, 0=关闭):", &mut self.idle_hide_input, duration_input_text(config.auto_hide_when_idle_secs)),
+            ("连续跳过几次后强制休息(0=关闭):", &mut self.skip_nag_input, config.skip_nag_threshold.to_string()),
+            ("暂停/未开始多久后提醒(分, 0=关闭):", &mut self.paused_nudge_input, config.paused_nudge_minutes.to_string()),
+            ("跳过休息后冷却(秒, 0=关闭):", &mut self.skip_cooldown_input, config.skip_cooldown_secs.to_string()),
+            ("可撤销跳过的时间(秒, 0=关闭):", &mut self.skip_undo_input, config.skip_undo_secs.to_string()),
+            ("推迟休息时长(分):", &mut self.snooze_input, config.snooze_minutes.to_string()),
+            ("每轮最多推迟(次, 0=关闭):", &mut self.max_snoozes_input, config.max_snoozes.to_string()),
+            ("每日专注上限(分, 0=关闭):", &mut self.daily_cap_input, config.daily_work_cap_minutes.to_string()),
+            ("统计自动保存(秒, 0=关闭):", &mut self.autosave_input, config.stats_autosave_secs.to_string()),
+            ("Webhook 地址:", &mut self.webhook_input, config.webhook_url.clone().unwrap_or_default()),
+            ("专注结束命令:", &mut self.work_cmd_input, config.on_work_end_cmd.clone().unwrap_or_default()),
+            ("休息结束命令:", &mut self.rest_cmd_input, config.on_rest_end_cmd.clone().unwrap_or_default()),
+            ("休息标题:", &mut self.overlay_title_input, config.overlay_title.clone()),
+            ("托盘提示格式:", &mut self.tooltip_template_input, config.tooltip_template.clone()),
+            ("声音文件夹:", &mut self.sounds_dir_input, config.sounds_dir.clone()),
+        ];
+        for (label, input, value) in fields {
+            if self.focused_widget != Some(settings_field_id(label)) {
+                *input = value;
+            }
+        }
+    }
+
+    fn handle_shortcuts(&mut self, ctx: &egui::Context) {
+        if ctx.memory(|m| m.focused()).is_some() {
+            return;
+        }
+        for (key, action) in SHORTCUTS {
+            if ctx.input(|i| i.key_pressed(key) && i.modifiers.is_none()) {
+                self.apply_shortcut(action);
+            }
+        }
+    }
+
+    // 休息提示里的推迟按钮；用完次数后保持显示但禁用，悬停说明原因
+    fn snooze_button(&mut self, ui: &mut egui::Ui, size: f32) -> Option<egui::Response> {
+        if self.config.max_snoozes == 0 || self.awaiting_ack || self.rest_lock_reason().is_some() {
+            return None;
+        }
+        let hold = self.config.hold_to_skip;
+        let label = egui::RichText::new(format!("{}推迟{}分钟", if hold { "按住" } else { "" }, self.config.snooze_minutes)).size(size);
+        let response = ui
+            .add_enabled(!self.snooze_limit_reached(), egui::Button::new(label))
+            .on_disabled_hover_text(format!("本轮已推迟 {} 次，达到上限", self.session_snoozes));
+        if hold {
+            let progress = self.hold_snooze(response.is_pointer_button_down_on());
+            if progress > 0.0 {
+                let mut fill = response.rect;
+                fill.set_width(fill.width() * progress);
+                ui.painter().rect_filled(fill, 4.0, ui.visuals().selection.bg_fill.gamma_multiply(0.5));
+                ui.ctx().request_repaint();
+            }
+        } else if response.clicked() {
+            self.snooze_rest();
+        }
+        Some(response)
+    }
+
+    fn render_skip_undo(&mut self, ctx: &egui::Context) {
+        let Some(undo) = &self.skip_undo else { return };
+        let left = undo.until.saturating_duration_since(self.now()).as_secs() + 1;
+        egui::TopBottomPanel::bottom("skip_undo").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(format!("已跳过休息 ({}秒)", left));
+                if ui.button("撤销").clicked() {
+                    self.undo_skip();
+                }
+            });
+        });
+        ctx.request_repaint_after(Duration::from_millis(250));
+    }
+
+    fn update_emojis(&mut self, ctx: &egui::Context) {
+        let dt = self.last_frame.elapsed().as_secs_f32();
+        self.last_frame = Instant::now();
+        let screen = ctx.input(|i| i.screen_rect);
+        // 切换全屏的过渡帧里 screen_rect 可能是 0，此时生成会让表情全挤在 x=0，跳过这一帧
+        let screen_ready = screen.is_positive() && screen.is_finite();
+        const PER_SPAWN: usize = 2;
+        let [min_speed, max_speed] = self.config.emoji_speed_range;
+        if self.state == AppState::Resting && screen_ready && fastrand::f32() < self.config.emoji_spawn_rate {
+            for _ in 0..PER_SPAWN.min(MAX_DROPS.saturating_sub(self.drops.len())) {
+                self.drops.push(EmojiDrop {
+                    emoji: Self::random_emoji(),
+                    x: fastrand::f32() * screen.width(),
+                    y: -30.0,
+                    speed: min_speed + fastrand::f32() * (max_speed - min_speed),
+                });
+            }
+        }
+        if self.is_long_rest && self.config.breathing_guide {
+            self.breathing_secs = (self.breathing_secs + dt) % BREATHING_CYCLE_SECS;
+        }
+        for d in &mut self.drops { d.y += d.speed * dt; }
+        self.drops.retain(|d| d.y < screen.bottom() + 50.0);
+    }
+
+    fn random_emoji() -> String {
+        let list = ["😀", "😂", "😎", "🤩", "😭", "🔥", "🍓", "🍉", "💎", "✨", "🎉", "❤️", "🚀"];
+        list[fastrand::usize(..list.len())].to_string()
+    }
+
+    // UI 渲染部分
+    // 蒙层的底色、标题色和倒计时颜色，主蒙层和其它显示器上的画面共用
+    fn overlay_colors(&self) -> (egui::Color32, egui::Color32, egui::Color32) {
+        if self.config.high_contrast {
+            (egui::Color32::BLACK, egui::Color32::YELLOW, egui::Color32::WHITE)
+        } else {
+            (egui::Color32::from_rgba_premultiplied(200, 240, 210, 240), egui::Color32::BLACK, egui::Color32::BLACK)
+        }
+    }
+
+    // 预览时显示休息的时长，而不是正在走的专注倒计时
+    fn overlay_time(&self) -> String {
+        if self.overlay_preview_until.is_some() {
+            format_duration(minutes_to_duration(self.current_rest_minutes()))
+        } else {
+            self.format_time()
+        }
+    }
+
+    fn render_overlay(&mut self, ctx: &egui::Context) {
+        let high_contrast = self.config.high_contrast;
+        let (fill, title_color, time_color) = self.overlay_colors();
+        let opacity = self.overlay_opacity();
+        if opacity < 1.0 {
+            ctx.request_repaint();
+        }
+        egui::CentralPanel::default()
+            .frame(egui::Frame { fill: fill.gamma_multiply(opacity), ..Default::default() })
+            .show(ctx, |ui| {
+                ui.set_opacity(opacity);
+                if !self.config.emojis_above_ui {
+                    self.render_emojis(ui.painter());
+                }
+                ui.vertical_centered(|ui| {
+                    ui.add_space(100.0);
+                    let title = self.rest_title();
+                    ui.label(egui::RichText::new(title).size(60.0).color(title_color));
+                    if let Some(kind) = self.current_reminder {
+                        ui.label(egui::RichText::new(kind.message()).size(28.0).color(title_color));
+                    }
+                    ui.label(egui::RichText::new(self.overlay_time()).size(100.0).strong().color(time_color));
+                    if let Some(task) = self.upcoming_task() {
+                        ui.label(egui::RichText::new(format!("接下来: {}", task)).size(32.0).color(title_color));
+                    }
+                    if self.is_long_rest && self.config.breathing_guide {
+                        self.render_breathing_guide(ui, title_color);
+                    }
+                    ui.add_space(50.0);
+                    if self.overlay_preview_until.is_some() {
+                        let response = ui.add(egui::Button::new(egui::RichText::new("结束预览").size(20.0)));
+                        self.overlay_button_area = Some(response.rect);
+                        if response.clicked() {
+                            self.end_overlay_preview();
+                        }
+                        return;
+                    }
+                    if self.awaiting_ack {
+                        self.overlay_button_area = None;
+                        let response = ui.add(egui::Button::new(egui::RichText::new("开始下一轮专注").size(28.0).strong()));
+                        let later = ui.add(egui::Button::new(egui::RichText::new("稍后再说").size(18.0)));
+                        self.overlay_button_area = Some(response.rect.union(later.rect));
+                        if response.clicked() {
+                            self.acknowledge_break(true);
+                        } else if later.clicked() {
+                            self.acknowledge_break(false);
+                        }
+                        return;
+                    }
+                    let hold = self.config.hold_to_skip;
+                    let skip_label = if hold { "按住跳过休息" } else { "跳过休息" };
+                    let skip_button = if high_contrast {
+                        // 高对比度下用黄底黑字粗边框，确保跳过按钮始终醒目
+                        egui::Button::new(egui::RichText::new(skip_label).size(28.0).strong().color(egui::Color32::BLACK))
+                            .fill(egui::Color32::YELLOW)
+                            .stroke(egui::Stroke::new(3.0, egui::Color32::WHITE))
+                    } else {
+                        egui::Button::new(egui::RichText::new(skip_label).size(20.0))
+                    };
+                    self.overlay_button_area = None;
+                    if self.open_ended_rest_done() {
+                        let response = ui.add(egui::Button::new(egui::RichText::new("结束休息").size(28.0).strong()));
+                        self.overlay_button_area = Some(response.rect);
+                        if response.clicked() {
+                            self.skip_rest();
+                        }
+                    } else if let Some(reason) = self.rest_lock_reason() {
+                        ui.label(egui::RichText::new(reason).size(20.0).color(title_color));
+                    } else {
+                        let response = ui.add(skip_button);
+                        self.overlay_button_area = Some(response.rect);
+                        if hold {
+                            let progress = self.hold_skip(response.is_pointer_button_down_on());
+                            if progress > 0.0 {
+                                // 从左往右填充，显示还要按多久
+                                let mut fill = response.rect;
+                                fill.set_width(fill.width() * progress);
+                                ui.painter().rect_filled(fill, 4.0, title_color.gamma_multiply(0.3));
+                                ui.ctx().request_repaint();
+                            }
+                        } else if response.clicked() {
+                            self.skip_rest();
+                        }
+                    }
+                    if let Some(response) = self.snooze_button(ui, 18.0) {
+                        self.overlay_button_area = Some(self.overlay_button_area.map_or(response.rect, |r| r.union(response.rect)));
+                    }
+                    if self.dev_mode {
+                        let response = ui.small_button("⏩ 2秒后切换");
+                        self.overlay_button_area = Some(self.overlay_button_area.map_or(response.rect, |r| r.union(response.rect)));
+                        if response.clicked() {
+                            self.fast_forward();
+                        }
+                    }
+                });
+            });
+    }
+
+    // 专注中的迷你倒计时: 无边框、置顶、半透明的小胶囊，按住即可拖动；
+    // 位置在小窗关闭 (专注结束/暂停) 时保存，避免拖动过程中反复写配置
+    fn render_mini_overlay(&mut self, ctx: &egui::Context) {
+        if !self.config.mini_overlay || self.state != AppState::Working {
+            if let Some(pos) = self.mini_overlay_pos.take() {
+                if self.config.mini_overlay_pos != Some([pos.x, pos.y]) {
+                    self.config.mini_overlay_pos = Some([pos.x, pos.y]);
+                    self.config.save();
+                }
+            }
+            return;
+        }
+        let mut builder = egui::ViewportBuilder::default()
+            .with_title("番茄钟倒计时")
+            .with_inner_size([130.0, 44.0])
+            .with_decorations(false)
+            .with_transparent(true)
+            .with_always_on_top()
+            .with_resizable(false)
+            .with_taskbar(false);
+        if let Some(pos) = self.config.mini_overlay_pos {
+            builder = builder.with_position(pos);
+        }
+        let text = self.format_time();
+        let color = self.config.accent_colors.working;
+        let viewport_pos = ctx.show_viewport_immediate(egui::ViewportId::from_hash_of("mini_overlay"), builder, |ctx, class| {
+            let pill = |ui: &mut egui::Ui| {
+                let label = egui::RichText::new(&text).size(24.0).strong().color(egui::Color32::from_rgb(color[0], color[1], color[2]));
+                let response = ui.add(egui::Label::new(label).sense(egui::Sense::drag()));
+                if response.drag_started() {
+                    ui.ctx().send_viewport_cmd(egui::ViewportCommand::StartDrag);
+                }
+            };
+            if class == egui::ViewportClass::Embedded {
+                // 后端不支持多窗口时退化为主窗口里的小浮窗
+                egui::Window::new("番茄钟倒计时").title_bar(false).resizable(false).show(ctx, pill);
+                return None;
+            }
+            egui::CentralPanel::default()
+                .frame(egui::Frame::none().fill(egui::Color32::from_black_alpha(160)).rounding(22.0).inner_margin(6.0))
+                .show(ctx, |ui| ui.vertical_centered(pill));
+            ctx.input(|i| i.viewport().outer_rect).map(|rect| rect.min)
+        });
+        if viewport_pos.is_some() {
+            self.mini_overlay_pos = viewport_pos;
+        }
+    }
+
+    // 其它显示器上的休息画面: 每个显示器一个无边框置顶窗口，只显示标题和倒计时，按钮留在主蒙层上；
+    // 颜色、淡入淡出和预览时的时长与主蒙层一致。创建时按主窗口的缩放估算位置，之后由 fit_window_to_monitor 按物理像素摆正一次
+    fn render_secondary_overlays(&mut self, ctx: &egui::Context) {
+        if !self.is_overlay_mode {
+            return;
+        }
+        let ppp = ctx.pixels_per_point();
+        let title = self.rest_title();
+        let time = self.overlay_time();
+        let (fill, title_color, time_color) = self.overlay_colors();
+        let opacity = self.overlay_opacity();
+        self.secondary_placed.resize(self.secondary_monitors.len(), false);
+        for (i, monitor) in self.secondary_monitors.iter().enumerate() {
+            // 标题各不相同，按标题找窗口
+            let window_title = format!("休息时间 {}", i + 1);
+            let builder = egui::ViewportBuilder::default()
+                .with_title(&window_title)
+                .with_position([monitor.left as f32 / ppp, monitor.top as f32 / ppp])
+                .with_inner_size([monitor.width as f32 / ppp, monitor.height as f32 / ppp])
+                .with_decorations(false)
+                .with_always_on_top()
+                .with_resizable(false)
+                .with_taskbar(false);
+            ctx.show_viewport_immediate(egui::ViewportId::from_hash_of(("rest_overlay", i)), builder, |ctx, class| {
+                if class == egui::ViewportClass::Embedded {
+                    // 后端不支持多窗口时只保留主蒙层
+                    return;
+                }
+                egui::CentralPanel::default().frame(egui::Frame::none().fill(fill.gamma_multiply(opacity))).show(ctx, |ui| {
+                    ui.set_opacity(opacity);
+                    ui.vertical_centered(|ui| {
+                        ui.add_space(ui.available_height() / 3.0);
+                        ui.label(egui::RichText::new(&title).size(60.0).color(title_color));
+                        ui.label(egui::RichText::new(&time).size(100.0).strong().color(time_color));
+                    });
+                });
+            });
+            if !self.secondary_placed[i] {
+                self.secondary_placed[i] = fit_window_to_monitor(&window_title, *monitor);
+            }
+        }
+    }
+
+    // 随呼吸节奏缩放的圆圈，下方提示当前是吸气/屏息/呼气
+    fn render_breathing_guide(&self, ui: &mut egui::Ui, color: egui::Color32) {
+        let (scale, hint) = breathing_phase(self.breathing_secs);
+        let (rect, _) = ui.allocate_exact_size(egui::vec2(180.0, 180.0), egui::Sense::hover());
+        let radius = egui::lerp(30.0..=85.0, scale);
+        ui.painter().circle(
+            rect.center(),
+            radius,
+            color.gamma_multiply(0.25),
+            egui::Stroke::new(3.0, color),
+        );
+        ui.label(egui::RichText::new(hint).size(24.0).color(color));
+    }
+
+    // 演示模式下的休息提示：窗口缩小置顶在屏幕角落，不抢焦点
+    fn render_rest_toast(&mut self, ctx: &egui::Context) {
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.vertical_centered(|ui| {
+                ui.label(egui::RichText::new(format!("{} {}", self.rest_title(), self.format_time())).size(24.0));
+                ui.add_space(8.0);
+                if self.awaiting_ack {
+                    if ui.button("开始下一轮专注").clicked() {
+                        self.acknowledge_break(true);
+                    }
+                } else if self.open_ended_rest_done() {
+                    if ui.button("结束休息").clicked() {
+                        self.skip_rest();
+                    }
+                } else if self.rest_lock_reason().is_none() && ui.button("跳过休息").clicked() {
+                    self.skip_rest();
+                }
+                self.snooze_button(ui, 14.0);
+            });
+        });
+    }
+
+    fn render_error_toast(&mut self, ctx: &egui::Context) {
+        const TOAST_SECS: u64 = 5;
+        let Some((message, shown_at)) = &self.error_toast else { return };
+        if shown_at.elapsed() >= Duration::from_secs(TOAST_SECS) {
+            self.error_toast = None;
+            return;
+        }
+        egui::TopBottomPanel::top("error_toast")
+            .frame(egui::Frame::default().fill(egui::Color32::from_rgb(200, 60, 60)).inner_margin(6.0))
+            .show(ctx, |ui| {
+                ui.label(egui::RichText::new(format!("⚠ {}", message)).color(egui::Color32::WHITE));
+            });
+    }
+
+    fn render_startup_warnings(&mut self, ctx: &egui::Context) {
+        if self.startup_warnings.is_empty() {
+            return;
+        }
+        egui::TopBottomPanel::top("startup_warnings")
+            .frame(egui::Frame::default().fill(egui::Color32::from_rgb(230, 160, 40)).inner_margin(6.0))
+            .show(ctx, |ui| {
+                for warning in &self.startup_warnings {
+                    ui.label(egui::RichText::new(format!("⚠ {}", warning)).color(egui::Color32::BLACK));
+                }
+                if ui.small_button("知道了").clicked() {
+                    self.startup_warnings.clear();
+                }
+            });
+    }
+
+    fn render_task_queue(&mut self, ui: &mut egui::Ui) {
+        let title = if self.task_queue.is_empty() { "任务队列".to_string() } else { format!("任务队列 ({})", self.task_queue.len()) };
+        ui.collapsing(title, |ui| {
+            let mut remove = None;
+            for (i, task) in self.task_queue.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.label(format!("{}. {}", i + 1, task));
+                    if ui.small_button("✖").clicked() {
+                        remove = Some(i);
+                    }
+                });
+            }
+            if let Some(i) = remove {
+                self.task_queue.remove(i);
+                self.persist_tasks();
+            }
+            ui.horizontal(|ui| {
+                let response = ui.text_edit_singleline(&mut self.task_queue_input);
+                let enter = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                let task = self.task_queue_input.trim().to_string();
+                if (ui.add_enabled(!task.is_empty(), egui::Button::new("添加")).clicked() || enter) && !task.is_empty() {
+                    self.task_queue.push(task);
+                    self.task_queue_input.clear();
+                    self.persist_tasks();
+                }
+            });
+        });
+    }
+
+    fn render_quick_start(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("快速开始:");
+            let response = ui.add(egui::TextEdit::singleline(&mut self.quick_start_input).desired_width(40.0).hint_text("45"));
+            ui.label("分钟");
+            let enter = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            if ui.button("开始").clicked() || enter {
+                match parse_minutes(&self.quick_start_input) {
+                    Ok(minutes) if minutes > 0 => {
+                        self.start_quick(minutes);
+                        self.quick_start_input.clear();
+                    }
+                    Ok(_) => self.show_error("专注时长至少 1 分钟".to_string()),
+                    Err(e) => self.show_error(e.to_string()),
+                }
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("对齐时钟:");
+            let now = chrono::Local::now().naive_local();
+            for step in [30, 60] {
+                let remaining = until_next_boundary(now, step);
+                let end = format_end_time(now, remaining, self.config.time_format_24h);
+                if ui.button(format!("专注到 {}", end)).clicked() {
+                    self.start_until(remaining);
+                }
+            }
+        });
+    }
+
+    fn render_sound_settings(&mut self, ui: &mut egui::Ui) {
+        let mut changed = ui.checkbox(&mut self.config.sound_enabled, "阶段切换时播放提示音").changed();
+        ui.add_enabled_ui(self.config.sound_enabled, |ui| {
+            let sounds_dir = self.config.resolved_sounds_dir();
+            let config = &mut self.config;
+            let events = [
+                ("开始专注:", &mut config.work_start_sound),
+                ("开始休息:", &mut config.break_sound),
+                ("休息结束:", &mut config.rest_end_sound),
+            ];
+            for (label, sound) in events {
+                ui.horizontal(|ui| {
+                    ui.label(label);
+                    let before = sound.clone();
+                    egui::ComboBox::from_id_salt(("event_sound", label))
+                        .selected_text(sound_label(&before))
+                        .show_ui(ui, |ui| {
+                            // 下拉框打开时重新扫描，新放进文件夹的声音不用重启
+                            self.available_sounds = sounds_dir.as_deref().map(scan_sounds).unwrap_or_default();
+                            ui.selectable_value(sound, String::new(), sound_label(""));
+                            for (id, label) in BUILTIN_SOUNDS {
+                                ui.selectable_value(sound, id.to_string(), label);
+                            }
+                            for name in &self.available_sounds {
+                                ui.selectable_value(sound, name.clone(), name);
+                            }
+                        });
+                    changed |= *sound != before;
+                    if ui.add_enabled(!sound.is_empty(), egui::Button::new("试听")).clicked() {
+                        play_sound(sound, sounds_dir.clone(), config.audio_device.clone());
+                    }
+                });
+            }
+            let fallback = self.config.sounds_dir.clone();
+            let config = &mut self.config;
+            changed |= settings_field(ui, "声音文件夹:", &mut self.sounds_dir_input, fallback, |text| {
+                config.sounds_dir = text.to_string();
+                Some(text.to_string())
+            });
+        });
+        ui.collapsing("声音诊断", |ui| {
+            if self.audio_devices.is_none() || ui.button("刷新设备列表").clicked() {
+                self.audio_devices = Some(list_audio_devices());
+            }
+            let devices = self.audio_devices.clone().unwrap_or(Ok(vec![]));
+            ui.label(audio_device_status(&devices, &self.config.audio_device));
+            let names = devices.unwrap_or_default();
+            ui.horizontal(|ui| {
+                ui.label("输出设备:");
+                let before = self.config.audio_device.clone();
+                egui::ComboBox::from_id_salt("audio_device")
+                    .selected_text(before.clone().unwrap_or_else(|| "系统默认".to_string()))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut self.config.audio_device, None, "系统默认");
+                        for name in &names {
+                            ui.selectable_value(&mut self.config.audio_device, Some(name.clone()), name);
+                        }
+                    });
+                changed |= self.config.audio_device != before;
+            });
+            if ui.button("播放测试音").clicked() {
+                play_sound("builtin:ding", None, self.config.audio_device.clone());
+            }
+        });
+        if changed {
+            self.config.save();
+        }
+    }
+
+    fn render_presets(&mut self, ui: &mut egui::Ui) {
+        let active = self.config.active_preset();
+        let mut apply = None;
+        let mut remove = None;
+        ui.horizontal_wrapped(|ui| {
+            ui.label("预设:");
+            for (i, preset) in self.config.presets.iter().enumerate() {
+                if ui.selectable_label(active == Some(i), preset.label()).clicked() {
+                    apply = Some(i);
+                }
+                if ui.small_button("✖").on_hover_text("删除这个预设").clicked() {
+                    remove = Some(i);
+                }
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut self.preset_name_input).hint_text("预设名称").desired_width(100.0));
+            let name = self.preset_name_input.trim().to_string();
+            if ui.add_enabled(!name.is_empty(), egui::Button::new("保存当前时长为预设")).clicked() {
+                self.config.presets.push(Preset::new(&name, self.config.work_minutes, self.config.rest_minutes));
+                self.preset_name_input.clear();
+                self.config.save();
+                self.rebuild_tray_presets();
+            }
+        });
+        if let Some(i) = apply {
+            self.apply_preset(i);
+        }
+        if let Some(i) = remove {
+            self.config.presets.remove(i);
+            self.config.save();
+            self.rebuild_tray_presets();
+        }
+    }
+
+    // 今天的时间轴：从第一段开始到最后一段结束 (按整点取整)，红色专注、绿色休息
+    fn render_timeline(&self, ui: &mut egui::Ui, date: chrono::NaiveDate) {
+        let sessions = self.stats.day(date).map_or(&[][..], |day| &day.sessions[..]);
+        let (Some(first), Some(last)) = (sessions.iter().map(|b| b.start).min(), sessions.iter().map(|b| b.end).max()) else {
+            ui.label("今天还没有时间轴记录");
+            return;
+        };
+        use chrono::Timelike;
+        let from = first.hour() * 3600;
+        let to = (last.num_seconds_from_midnight().div_ceil(3600) * 3600).max(from + 3600);
+        let (rect, response) = ui.allocate_exact_size(egui::vec2(ui.available_width(), 24.0), egui::Sense::hover());
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 3.0, ui.visuals().extreme_bg_color);
+        let x_at = |time: chrono::NaiveTime| {
+            let t = (time.num_seconds_from_midnight().clamp(from, to) - from) as f32 / (to - from) as f32;
+            egui::lerp(rect.left()..=rect.right(), t)
+        };
+        let [wr, wg, wb] = self.config.accent_colors.working;
+        let [rr, rg, rb] = self.config.accent_colors.resting;
+        let mut hovered = None;
+        for block in sessions {
+            let block_rect = egui::Rect::from_x_y_ranges(x_at(block.start)..=x_at(block.end).max(x_at(block.start) + 1.0), rect.y_range());
+            let color = match block.kind {
+                SessionKind::Work => egui::Color32::from_rgb(wr, wg, wb),
+                SessionKind::Rest => egui::Color32::from_rgb(rr, rg, rb),
+            };
+            painter.rect_filled(block_rect, 0.0, color);
+            if response.hover_pos().is_some_and(|pos| block_rect.contains(pos)) {
+                hovered = Some(block);
+            }
+        }
+        ui.horizontal(|ui| {
+            ui.small(format!("{:02}:00", from / 3600));
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| ui.small(format!("{:02}:00", to / 3600)));
+        });
+        if let Some(block) = hovered {
+            let kind = match block.kind {
+                SessionKind::Work => "专注",
+                SessionKind::Rest => "休息",
+            };
+            let minutes = (block.end - block.start).num_minutes();
+            let task = block.task.as_deref().map(|t| format!("\n任务: {}", t)).unwrap_or_default();
+            response.on_hover_text(format!("{} {}-{} ({} 分钟){}", kind, block.start.format("%H:%M"), block.end.format("%H:%M"), minutes, task));
+        }
+    }
+
+    // 最近几段一排小圆点: 完成的专注绿色实心，完成的休息用暂停色，跳过、放弃和中断的空心；悬停显示时长和任务
+    fn render_session_dots(&self, ui: &mut egui::Ui) {
+        let recent = self.stats.recent_sessions(RECENT_SESSION_DOTS);
+        if recent.is_empty() {
+            return;
+        }
+        let colors = self.config.accent_colors;
+        let dot_color = |kind| {
+            let [r, g, b] = colors.session_dot(kind);
+            egui::Color32::from_rgb(r, g, b)
+        };
+        ui.horizontal(|ui| {
+            ui.spacing_mut().item_spacing.x = 3.0;
+            for block in recent {
+                let (rect, response) = ui.allocate_exact_size(egui::vec2(10.0, 10.0), egui::Sense::hover());
+                let color = dot_color(block.kind);
+                if block.skipped || block.in_progress {
+                    ui.painter().circle_stroke(rect.center(), 3.5, egui::Stroke::new(1.5, color));
+                } else {
+                    ui.painter().circle_filled(rect.center(), 4.0, color);
+                }
+                let kind = match (block.kind, block.skipped) {
+                    (SessionKind::Work, _) if block.in_progress => "中断的专注",
+                    (SessionKind::Rest, _) if block.in_progress => "中断的休息",
+                    (SessionKind::Work, false) => "专注",
+                    (SessionKind::Work, true) => "放弃的专注",
+                    (SessionKind::Rest, false) => "休息",
+                    (SessionKind::Rest, true) => "跳过的休息",
+                };
+                let minutes = (block.end - block.start).num_minutes();
+                let task = block.task.as_deref().map(|t| format!("\n任务: {}", t)).unwrap_or_default();
+                response.on_hover_text(format!("{} {} 分钟{}", kind, minutes, task));
+            }
+        });
+    }
+
+    // 流水里的完成/放弃次数，今日和本周各一行；流水没有打开时不显示
+    fn render_session_totals(&mut self, ui: &mut egui::Ui, date: chrono::NaiveDate) {
+        if !self.session_log.is_open() {
+            return;
+        }
+        if self.session_totals.is_none_or(|(cached, _, _)| cached != date) {
+            let day = self.session_log.totals(date, date);
+            let week = self.session_log.totals(week_start(date), date);
+            self.session_totals = Some((date, day, week));
+        }
+        let Some((_, day, week)) = self.session_totals else { return };
+        for (label, totals) in [("今日", day), ("本周", week)] {
+            ui.label(format!(
+                "{}流水: 专注完成 {} 次、放弃 {} 次，休息完成 {} 次、跳过 {} 次",
+                label, totals.finished_work, totals.abandoned_work, totals.finished_rest, totals.skipped_rest
+            ));
+        }
+    }
+
+    // 例如 "今日计划 4 / 8 🍅" 加一条进度条；没有计划时不显示
+    fn render_plan_progress(&self, ui: &mut egui::Ui, date: chrono::NaiveDate, width: f32) {
+        let plan = self.stats.planned_on(date);
+        if plan == 0 {
+            return;
+        }
+        let done = self.stats.completed_on(date);
+        let fraction = (done as f32 / plan as f32).min(1.0);
+        ui.add(egui::ProgressBar::new(fraction).desired_width(width).text(format!("今日计划 {} / {} 🍅", done, plan)));
+    }
+
+    fn render_stats(&mut self, ui: &mut egui::Ui) {
+        let date = today();
+        let (completed, focus_secs) = self.stats.day(date).map_or((0, 0), |day| (day.completed_work, day.focus_secs));
+        ui.label(format!("今日完成 {} 个番茄，专注 {} 分钟", completed, focus_secs / 60));
+        let (week_completed, week_secs) = self.stats.week_totals(date);
+        ui.label(format!("本周完成 {} 个番茄，专注 {} 分钟", week_completed, week_secs / 60));
+        self.render_session_totals(ui, date);
+        ui.horizontal(|ui| {
+            ui.label("今日计划:");
+            let mut plan = self.stats.planned_on(date);
+            let response = ui.add(egui::DragValue::new(&mut plan).range(0..=24).suffix(" 个番茄"));
+            if response.on_hover_text("0 表示不设计划；新的一天默认沿用上次的计划").changed() {
+                self.stats.set_plan(date, plan);
+                self.stats.save();
+            }
+        });
+        self.render_plan_progress(ui, date, 200.0);
+        let rest_secs = self.stats.day(date).map_or(0, |day| day.rest_secs);
+        let snoozes = self.stats.day(date).map_or(0, |day| day.snoozes);
+        ui.label(format!("今日休息 {} 分钟，推迟 {} 次", rest_secs / 60, snoozes));
+        ui.label(format!("累计完成 {} 个番茄", self.stats.total_completed));
+        let score = self.stats.day(date).map_or(0, DayStats::focus_score);
+        ui.label(format!("今日专注分: {}", score)).on_hover_text(format!(
+            "每完成一个番茄 +{}，跳过休息 -{}，推迟休息 -{}，最低 0 分",
+            SCORE_COMPLETED, SCORE_SKIPPED, SCORE_SNOOZED
+        ));
+        self.render_timeline(ui, date);
+        if let Some(day) = self.stats.day(date).filter(|day| !day.reminders.is_empty()) {
+            let counts: Vec<String> = day.reminders.iter().map(|(kind, n)| format!("{}{} {}", kind.icon(), kind.label(), n)).collect();
+            ui.label(format!("今日提醒: {}", counts.join("  ")));
+        }
+        if self.config.water_goal_cups > 0 {
+            // 按日期存，过了零点自然从 0 开始
+            let cups = self.stats.day(date).map_or(0, |day| day.water_cups);
+            ui.horizontal(|ui| {
+                ui.label(format!("今日饮水 {}/{} 杯", cups, self.config.water_goal_cups));
+                if ui.small_button("+1杯").clicked() {
+                    self.stats.record_water(date);
+                    self.stats.save();
+                }
+            });
+        }
+        if self.config.track_apps {
+            let apps = self.stats.top_apps(date, 5);
+            if apps.is_empty() {
+                ui.label("今日还没有程序用时记录");
+            }
+            for (name, secs) in apps {
+                ui.label(format!("{}  {} 分钟", name, secs / 60));
+            }
+        }
+        if !self.recent_notes.is_empty() {
+            ui.separator();
+            ui.label("最近的记录:");
+            for note in self.recent_notes.iter().rev() {
+                // 只显示 RFC 3339 中的 月-日 时:分
+                let when = note.timestamp.get(5..16).unwrap_or(&note.timestamp).replace('T', " ");
+                let task = note.task.as_deref().map(|t| format!("[{}] ", t)).unwrap_or_default();
+                ui.label(format!("{} {}{}", when, task, note.note));
+            }
+        }
+    }
+
+    pub(crate) fn render_main(&mut self, ctx: &egui::Context) {
+        self.focused_widget = ctx.memory(|m| m.focused());
+        if let Some(milestone) = self.pending_celebration.take() {
+            self.spawn_celebration(ctx, milestone);
+        }
+        if self.celebration.is_some_and(|(_, at)| at.elapsed() > Duration::from_secs(6)) {
+            self.celebration = None;
+        }
+        // 休息蒙层期间不绘制主界面，等回到主界面时已经过期，不会和蒙层抢画面
+        if let Some((at, burst)) = self.work_done {
+            if self.now().saturating_duration_since(at) > WORK_DONE_TOAST {
+                self.work_done = None;
+            } else if !burst {
+                self.spawn_burst(ctx, &["🎉", "👏", "✨"], 15);
+                self.work_done = Some((at, true));
+            }
+        }
+        self.render_error_toast(ctx);
+        self.render_startup_warnings(ctx);
+        self.render_skip_undo(ctx);
+        self.render_auto_start_confirm(ctx);
+        // 窗口放大或最大化时内容保持一栏宽度居中
+        let side = ((ctx.screen_rect().width() - MAIN_CONTENT_WIDTH) / 2.0).max(0.0);
+        let frame = egui::Frame::central_panel(&ctx.style());
+        let frame = frame.inner_margin(egui::Margin { left: frame.inner_margin.left + side, right: frame.inner_margin.right + side, ..frame.inner_margin });
+        egui::CentralPanel::default().frame(frame).show(ctx, |ui| {
+            if !self.config.emojis_above_ui {
+                self.render_emojis(ui.painter());
+            }
+            ui.add_space(20.0);
+            let high_contrast = self.config.high_contrast;
+            let time_color = match (&self.state, high_contrast) {
+                (AppState::Working, true) => egui::Color32::YELLOW,
+                (_, true) => egui::Color32::WHITE,
+                (state, false) => {
+                    let [r, g, b] = self.config.accent_colors.for_state(*state);
+                    egui::Color32::from_rgb(r, g, b)
+                }
+            };
+            ui.vertical_centered(|ui| {
+                ui.label(egui::RichText::new(self.format_time()).size(60.0).color(time_color));
+                let status = match self.state {
+                    AppState::Ready => "准备开始",
+                    AppState::Working => "🔥 专注中",
+                    AppState::Resting => "☕ 休息中",
+                    AppState::Paused => "⏸ 已暂停",
+                };
+                if high_contrast {
+                    ui.label(egui::RichText::new(status).size(28.0).strong().color(egui::Color32::YELLOW));
+                } else {
+                    ui.label(status);
+                }
+                if self.start_time.is_some() {
+                    let now = chrono::Local::now().naive_local();
+                    ui.label(format!("预计 {} 结束", format_end_time(now, self.time_remaining, self.config.time_format_24h)));
+                }
+                if let Some(paused) = self.paused_for() {
+                    ui.label(format!("已暂停 {}", format_elapsed_minutes(paused)));
+                }
+                self.render_plan_progress(ui, today(), 160.0);
+                self.render_session_dots(ui);
+                if self.over_daily_cap() {
+                    let cap = self.config.daily_work_cap_minutes;
+                    let amount = if cap.is_multiple_of(60) { format!("{} 小时", cap / 60) } else { format!("{} 分钟", cap) };
+                    ui.label(egui::RichText::new(format!("⚠ 今日专注已超过 {}，注意休息", amount)).strong().color(egui::Color32::from_rgb(230, 150, 40)));
+                }
+                if self.off_hours {
+                    ui.label(egui::RichText::new("🌙 非工作时间，提醒已暂停").color(egui::Color32::from_rgb(120, 120, 200)));
+                }
+                if let Some((milestone, _)) = self.celebration {
+                    ui.label(egui::RichText::new(format!("🏆 累计完成 {} 个番茄！", milestone)).size(20.0).strong());
+                }
+                if self.work_done.is_some() {
+                    ui.label(egui::RichText::new("做得好! 🎉").size(18.0).strong());
+                }
+            });
+            ui.vertical_centered(|ui| self.render_long_rest_progress(ui));
+            ui.horizontal(|ui| {
+                ui.label("当前任务:");
+                if ui.text_edit_singleline(&mut self.current_task).lost_focus() {
+                    self.persist_tasks();
+                }
+            });
+            self.render_task_queue(ui);
+            ui.add_space(20.0);
+            let allowed = !self.off_hours;
+            let rest_lock = self.rest_lock_reason();
+            match self.state {
+                AppState::Ready => {
+                    let cooldown = self.skip_cooldown_left();
+                    ui.vertical_centered_justified(|ui| {
+                        if ui.add_enabled(allowed && cooldown.is_none(), egui::Button::new("开始专注")).clicked() { self.user_start_work(); }
+                    });
+                    if let Some(left) = cooldown {
+                        ui.vertical_centered(|ui| ui.label(format!("刚跳过了休息，{} 秒后才能开始专注", left.as_secs() + 1)));
+                        ui.ctx().request_repaint_after(Duration::from_millis(250));
+                    }
+                }
+                AppState::Paused => {
+                    ui.columns(3, |cols| {
+                        if cols[0].add_enabled(allowed, egui::Button::new("继续")).clicked() { self.resume(); }
+                        if cols[1].add_enabled(allowed, egui::Button::new("重新开始")).clicked() { self.user_start_work(); }
+                        let can_rest = allowed && !self.skip_locked();
+                        if cols[2].add_enabled(can_rest, egui::Button::new("休息一下")).clicked() { self.user_start_rest(); }
+                    });
+                }
+                AppState::Working | AppState::Resting if self.skip_locked() => {
+                    ui.vertical_centered(|ui| ui.label("🔒 今天的第一个番茄，完成前不可暂停或跳过"));
+                }
+                AppState::Resting if rest_lock.is_some() => {
+                    ui.vertical_centered(|ui| ui.label(rest_lock.unwrap_or_default()));
+                }
+                AppState::Working | AppState::Resting => {
+                    ui.columns(3, |cols| {
+                        if cols[0].add_enabled(allowed, egui::Button::new("开始专注")).clicked() { self.user_start_work(); }
+                        if cols[1].button("暂停").clicked() { self.pause(); }
+                        if cols[2].add_enabled(allowed, egui::Button::new("休息一下")).clicked() { self.user_start_rest(); }
+                    });
+                }
+            }
+            if allowed && !self.skip_locked() && self.skip_cooldown_left().is_none() {
+                self.render_quick_start(ui);
+            }
+            if self.dev_mode {
+                ui.vertical_centered(|ui| {
+                    let running = self.start_time.is_some();
+                    if ui.add_enabled(running, egui::Button::new("⏩ 2秒后切换").small()).clicked() {
+                        self.fast_forward();
+                    }
+                });
+            }
+            ui.separator();
+            let settings = ui.collapsing("设置", |ui| {
+                let config = &mut self.config;
+                // 时长输入统一用 parse_duration (只存整分钟的用 parse_minutes，以秒为单位的用 parse_seconds)，解析失败时输入框恢复原值并提示原因
+                let parse_error = std::cell::Cell::new(None);
+                let parsed = |text: &str| parse_duration(text).map_err(|e| parse_error.set(Some(e))).ok();
+                let minutes = |text: &str| parse_minutes(text).map_err(|e| parse_error.set(Some(e))).ok();
+                let seconds = |text: &str| parse_seconds(text).map_err(|e| parse_error.set(Some(e))).ok();
+                let mut committed = settings_field(ui, "专注时长(分):", &mut self.work_input, config.work_minutes.to_string(), |text| {
+                    config.set_work_minutes(minutes(text)?);
+                    Some(config.work_minutes.to_string())
+                });
+                committed |= settings_field(ui, "休息时长(分):", &mut self.rest_input, config.rest_minutes.to_string(), |text| {
+                    config.set_rest_minutes(minutes(text)?);
+                    Some(config.rest_minutes.to_string())
+                });
+                // 常用时长快捷按钮，和输入框一样立即保存
+                let mut work_chip = false;
+                ui.horizontal(|ui| {
+                    ui.label("专注:");
+                    for minutes in WORK_CHIPS {
+                        if ui.selectable_label(config.work_minutes == minutes, format!("{}分", minutes)).clicked() {
+                            config.set_work_minutes(minutes);
+                            self.work_input = config.work_minutes.to_string();
+                            work_chip = true;
+                        }
+                    }
+                    ui.label("休息:");
+                    for minutes in REST_CHIPS {
+                        if ui.selectable_label(config.rest_minutes == minutes, format!("{}分", minutes)).clicked() {
+                            config.set_rest_minutes(minutes);
+                            self.rest_input = config.rest_minutes.to_string();
+                            committed = true;
+                        }
+                    }
+                });
+                if work_chip && self.state == AppState::Ready {
+                    self.time_remaining = minutes_to_duration(config.work_minutes);
+                }
+                committed |= work_chip;
+                committed |= settings_field(ui, "长休息时长(分):", &mut self.long_rest_input, config.long_rest_minutes.to_string(), |text| {
+                    config.set_long_rest_minutes(minutes(text)?);
+                    Some(config.long_rest_minutes.to_string())
+                });
+                committed |= settings_field(ui, "每几个番茄长休息(0=关闭):", &mut self.cycles_input, config.cycles_before_long_rest.to_string(), |text| {
+                    config.cycles_before_long_rest = text.parse::<u64>().ok()?.min(12);
+                    Some(config.cycles_before_long_rest.to_string())
+                });
+                let idle_fallback = duration_input_text(config.auto_hide_when_idle_secs);
+                committed |= settings_field(ui, "空闲自动隐藏(分, 0=关闭):", &mut self.idle_hide_input, idle_fallback, |text| {
+                    config.auto_hide_when_idle_secs = parsed(text)?.as_secs();
+                    Some(duration_input_text(config.auto_hide_when_idle_secs))
+                });
+                committed |= settings_field(ui, "连续跳过几次后强制休息(0=关闭):", &mut self.skip_nag_input, config.skip_nag_threshold.to_string(), |text| {
+                    config.skip_nag_threshold = text.parse::<u32>().ok()?.min(20);
+                    Some(config.skip_nag_threshold.to_string())
+                });
+                committed |= settings_field(ui, "跳过休息后冷却(秒, 0=关闭):", &mut self.skip_cooldown_input, config.skip_cooldown_secs.to_string(), |text| {
+                    config.skip_cooldown_secs = seconds(text)?.min(600);
+                    Some(config.skip_cooldown_secs.to_string())
+                });
+                committed |= settings_field(ui, "可撤销跳过的时间(秒, 0=关闭):", &mut self.skip_undo_input, config.skip_undo_secs.to_string(), |text| {
+                    config.skip_undo_secs = seconds(text)?.min(30);
+                    Some(config.skip_undo_secs.to_string())
+                });
+                committed |= settings_field(ui, "推迟休息时长(分):", &mut self.snooze_input, config.snooze_minutes.to_string(), |text| {
+                    config.snooze_minutes = minutes(text)?.clamp(1, MAX_SESSION_MINUTES);
+                    Some(config.snooze_minutes.to_string())
+                });
+                committed |= settings_field(ui, "每轮最多推迟(次, 0=关闭):", &mut self.max_snoozes_input, config.max_snoozes.to_string(), |text| {
+                    config.max_snoozes = text.parse::<u32>().ok()?.min(10);
+                    Some(config.max_snoozes.to_string())
+                });
+                committed |= settings_field(ui, "暂停/未开始多久后提醒(分, 0=关闭):", &mut self.paused_nudge_input, config.paused_nudge_minutes.to_string(), |text| {
+                    config.paused_nudge_minutes = minutes(text)?.min(MAX_SESSION_MINUTES);
+                    Some(config.paused_nudge_minutes.to_string())
+                });
+                committed |= settings_field(ui, "统计自动保存(秒, 0=关闭):", &mut self.autosave_input, config.stats_autosave_secs.to_string(), |text| {
+                    config.stats_autosave_secs = seconds(text)?.min(3600);
+                    Some(config.stats_autosave_secs.to_string())
+                });
+                committed |= settings_field(ui, "每日专注上限(分, 0=关闭):", &mut self.daily_cap_input, config.daily_work_cap_minutes.to_string(), |text| {
+                    config.daily_work_cap_minutes = minutes(text)?.min(MAX_SESSION_MINUTES);
+                    Some(config.daily_work_cap_minutes.to_string())
+                });
+                // 地址和标题允许清空：清空即关闭 Webhook / 恢复默认标题
+                committed |= settings_field(ui, "Webhook 地址:", &mut self.webhook_input, config.webhook_url.clone().unwrap_or_default(), |text| {
+                    config.webhook_url = (!text.is_empty()).then(|| text.to_string());
+                    Some(text.to_string())
+                });
+                committed |= settings_field(ui, "专注结束命令:", &mut self.work_cmd_input, config.on_work_end_cmd.clone().unwrap_or_default(), |text| {
+                    config.on_work_end_cmd = (!text.is_empty()).then(|| text.to_string());
+                    Some(text.to_string())
+                });
+                committed |= settings_field(ui, "休息结束命令:", &mut self.rest_cmd_input, config.on_rest_end_cmd.clone().unwrap_or_default(), |text| {
+                    config.on_rest_end_cmd = (!text.is_empty()).then(|| text.to_string());
+                    Some(text.to_string())
+                });
+                committed |= settings_field(ui, "休息标题:", &mut self.overlay_title_input, config.overlay_title.clone(), |text| {
+                    config.overlay_title = if text.is_empty() { DEFAULT_OVERLAY_TITLE.to_string() } else { text.to_string() };
+                    Some(config.overlay_title.clone())
+                });
+                committed |= settings_field(ui, "托盘提示格式:", &mut self.tooltip_template_input, config.tooltip_template.clone(), |text| {
+                    config.tooltip_template = if text.is_empty() { DEFAULT_TOOLTIP_TEMPLATE.to_string() } else { text.to_string() };
+                    Some(config.tooltip_template.clone())
+                });
+                ui.small("可用: {state} 状态  {time} 剩余时间  {task} 当前任务  {today_count} 今日番茄数");
+                if let Some(e) = parse_error.take() {
+                    self.show_error(e.to_string());
+                }
+                if committed {
+                    self.config.save();
+                    self.rebuild_tray_presets();
+                }
+                self.render_presets(ui);
+                self.render_sound_settings(ui);
+                if ui.checkbox(&mut self.config.presentation_mode, "演示模式 (休息时仅在角落小窗提示)").changed() {
+                    self.config.save();
+                }
+                if cfg!(target_os = "windows")
+                    && ui.checkbox(&mut self.config.auto_presentation_mode, "麦克风/摄像头使用中时自动演示模式").changed()
+                {
+                    self.config.save();
+                }
+                if cfg!(target_os = "windows")
+                    && ui.checkbox(&mut self.config.track_apps, "统计专注时的前台程序")
+                        .on_hover_text("只记录进程名，数据仅保存在本机的 stats.json")
+                        .changed()
+                {
+                    self.config.save();
+                }
+                if ui.checkbox(&mut self.config.ask_session_note, "专注结束时记录一句话").changed() {
+                    self.config.save();
+                }
+                if ui.checkbox(&mut self.config.celebrate_work_done, "专注完成时显示祝贺").changed() {
+                    self.config.save();
+                }
+                if ui.checkbox(&mut self.config.require_break_ack, "休息结束后需要确认才开始下一轮").changed() {
+                    self.config.save();
+                }
+                if ui.checkbox(&mut self.config.lock_first_session, "每天第一个番茄不可暂停/跳过").changed() {
+                    self.config.save();
+                }
+                if ui.checkbox(&mut self.config.start_working_on_launch, "启动后自动开始专注").changed() {
+                    self.config.save();
+                }
+                if ui.checkbox(&mut self.config.start_hidden, "启动时隐藏到托盘").changed() {
+                    self.config.save();
+                }
+                if ui.checkbox(&mut self.config.cap_forces_long_rest, "超过每日专注上限后强制长休息").changed() {
+                    self.config.save();
+                }
+                if ui.checkbox(&mut self.config.tooltip_show_stats, "托盘提示显示今日番茄数").changed() {
+                    self.config.save();
+                }
+                if ui.checkbox(&mut self.config.time_format_24h, "结束时间使用 24 小时制").changed() {
+                    self.config.save();
+                }
+                if ui.checkbox(&mut self.config.show_next_task, "休息快结束时显示下一个任务").changed() {
+                    self.config.save();
+                }
+                if ui.checkbox(&mut self.config.idle_repaint_pause, "低功耗: 暂停并隐藏时停止刷新界面").changed() {
+                    self.config.save();
+                }
+                if ui.checkbox(&mut self.config.minimize_to_taskbar, "关闭时最小化到任务栏 (不隐藏到托盘)").changed() {
+                    self.config.save();
+                }
+                ui.horizontal(|ui| {
+                    let mut changed = ui.checkbox(&mut self.config.allow_maximize, "允许最大化").changed();
+                    changed |= ui.checkbox(&mut self.config.allow_resize, "允许调整窗口大小").changed();
+                    ui.label(egui::RichText::new("(重启后生效)").small().weak());
+                    if changed {
+                        self.config.save();
+                    }
+                });
+                ui.horizontal(|ui| {
+                    let mut changed = ui.checkbox(&mut self.config.auto_minimize_on_work, "开始专注后自动收起窗口").changed();
+                    let delay = egui::DragValue::new(&mut self.config.auto_minimize_delay_secs).range(0..=60).suffix(" 秒后");
+                    changed |= ui.add_enabled(self.config.auto_minimize_on_work, delay).changed();
+                    if changed {
+                        self.config.save();
+                    }
+                });
+                if ui.checkbox(&mut self.config.steal_focus_on_show, "从托盘唤醒时抢占焦点").changed() {
+                    STEAL_FOCUS_ON_SHOW.store(self.config.steal_focus_on_show, Ordering::SeqCst);
+                    self.config.save();
+                }
+                ui.horizontal(|ui| {
+                    ui.label("休息强度:");
+                    let before = self.config.intensity;
+                    egui::ComboBox::from_id_salt("intensity")
+                        .selected_text(before.label())
+                        .show_ui(ui, |ui| {
+                            for intensity in Intensity::ALL {
+                                ui.selectable_value(&mut self.config.intensity, intensity, intensity.label());
+                            }
+                        });
+                    if self.config.intensity != before {
+                        self.config.save();
+                    }
+                });
+                if ui.add_enabled(self.state != AppState::Resting, egui::Button::new("预览休息界面"))
+                    .on_hover_text(format!("按当前设置显示休息界面 {} 秒，不影响计时", OVERLAY_PREVIEW.as_secs()))
+                    .clicked()
+                {
+                    self.preview_overlay();
+                }
+                ui.horizontal(|ui| {
+                    ui.label("休息窗口:");
+                    let before = self.config.rest_window_mode;
+                    egui::ComboBox::from_id_salt("rest_window_mode")
+                        .selected_text(before.label())
+                        .show_ui(ui, |ui| {
+                            for mode in RestWindowMode::ALL {
+                                ui.selectable_value(&mut self.config.rest_window_mode, mode, mode.label());
+                            }
+                        });
+                    if self.config.rest_window_mode != before {
+                        self.config.save();
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label("休息计时:");
+                    let before = self.config.rest_count_direction;
+                    egui::ComboBox::from_id_salt("rest_count_direction")
+                        .selected_text(before.label())
+                        .show_ui(ui, |ui| {
+                            for direction in RestCountDirection::ALL {
+                                ui.selectable_value(&mut self.config.rest_count_direction, direction, direction.label());
+                            }
+                        });
+                    if self.config.rest_count_direction != before {
+                        self.config.save();
+                    }
+                });
+                if ui.checkbox(&mut self.config.open_ended_rest, "不限时休息 (手动结束)").changed() {
+                    self.config.save();
+                }
+                ui.horizontal(|ui| {
+                    ui.label("休息结束后:");
+                    let before = self.config.rest_end_action;
+                    egui::ComboBox::from_id_salt("rest_end_action")
+                        .selected_text(before.label())
+                        .show_ui(ui, |ui| {
+                            for action in RestEndAction::ALL {
+                                ui.selectable_value(&mut self.config.rest_end_action, action, action.label());
+                            }
+                        });
+                    if self.config.rest_end_action != before {
+                        self.config.save();
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label("托盘左键点击:");
+                    let before = self.config.tray_left_click_action;
+                    egui::ComboBox::from_id_salt("tray_left_click_action")
+                        .selected_text(before.label())
+                        .show_ui(ui, |ui| {
+                            for action in TrayClickAction::ALL {
+                                ui.selectable_value(&mut self.config.tray_left_click_action, action, action.label());
+                            }
+                        });
+                    if self.config.tray_left_click_action != before {
+                        let shows = self.config.tray_left_click_action != TrayClickAction::PauseResume;
+                        TRAY_CLICK_SHOWS.store(shows, Ordering::SeqCst);
+                        self.config.save();
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label("休息蒙层显示在:");
+                    let before = self.config.overlay_monitor;
+                    egui::ComboBox::from_id_salt("overlay_monitor")
+                        .selected_text(before.label())
+                        .show_ui(ui, |ui| {
+                            let choice = &mut self.config.overlay_monitor;
+                            ui.selectable_value(choice, OverlayMonitor::Current, OverlayMonitor::Current.label());
+                            ui.selectable_value(choice, OverlayMonitor::Primary, OverlayMonitor::Primary.label());
+                            for i in 0..self.monitor_count {
+                                ui.selectable_value(choice, OverlayMonitor::Index(i), OverlayMonitor::Index(i).label());
+                            }
+                        });
+                    if self.config.overlay_monitor != before {
+                        self.config.save();
+                    }
+                });
+                if self.monitor_count > 1
+                    && ui.checkbox(&mut self.config.overlay_all_monitors, "其它显示器也显示休息画面").changed()
+                {
+                    self.config.save();
+                }
+                if cfg!(target_os = "windows")
+                    && ui.checkbox(&mut self.config.suppress_during_fullscreen, "其它程序全屏时推迟休息 (游戏/视频)").changed()
+                {
+                    self.config.save();
+                }
+                if cfg!(target_os = "windows")
+                    && ui.checkbox(&mut self.config.overlay_click_through, "(实验) 休息蒙层鼠标穿透")
+                        .on_hover_text("只有跳过按钮附近可以点击，其它位置的点击会落到下面的窗口")
+                        .changed()
+                {
+                    self.config.save();
+                }
+                if ui.checkbox(&mut self.config.mini_overlay, "专注时显示迷你倒计时小窗").changed() {
+                    self.config.save();
+                }
+                if ui.checkbox(&mut self.config.overlay_borderless, "休息蒙层不独占全屏 (可 Alt+Tab 切走)")
+                    .on_hover_text("用无边框置顶窗口铺满屏幕，任务栏仍然可用")
+                    .changed()
+                {
+                    self.config.save();
+                }
+                if ui.checkbox(&mut self.config.smooth_transitions, "休息蒙层淡入淡出").changed() {
+                    self.config.save();
+                }
+                let hold_label = format!("跳过或推迟休息需按住 {} 秒", HOLD_TO_SKIP.as_secs());
+                if ui.checkbox(&mut self.config.hold_to_skip, hold_label).changed() {
+                    self.config.save();
+                }
+                if ui.checkbox(&mut self.config.breathing_guide, "长休息时显示呼吸引导").changed() {
+                    self.config.save();
+                }
+                ui.horizontal(|ui| {
+                    ui.label("表情密度:");
+                    let mut rate = self.config.emoji_spawn_rate;
+                    if ui.add(egui::Slider::new(&mut rate, 0.0..=1.0)).changed() {
+                        self.config.set_emoji_spawn_rate(rate);
+                        self.config.save();
+                    }
+                });
+                if ui.checkbox(&mut self.config.emojis_above_ui, "表情显示在按钮上层").changed() {
+                    self.config.save();
+                }
+                ui.horizontal(|ui| {
+                    ui.label("下落速度:");
+                    let [mut min, mut max] = self.config.emoji_speed_range;
+                    let mut changed = ui.add(egui::DragValue::new(&mut min).range(EMOJI_SPEED_BOUNDS)).changed();
+                    ui.label("-");
+                    changed |= ui.add(egui::DragValue::new(&mut max).range(EMOJI_SPEED_BOUNDS)).changed();
+                    if changed {
+                        self.config.set_emoji_speed_range([min, max]);
+                        self.config.save();
+                    }
+                });
+                ui.horizontal(|ui| {
+                    let colors = &mut self.config.accent_colors;
+                    ui.label("专注");
+                    let mut changed = ui.color_edit_button_srgb(&mut colors.working).changed();
+                    ui.label("休息");
+                    changed |= ui.color_edit_button_srgb(&mut colors.resting).changed();
+                    ui.label("暂停");
+                    changed |= ui.color_edit_button_srgb(&mut colors.paused).changed();
+                    if ui.small_button("恢复默认").clicked() {
+                        *colors = AccentColors::default();
+                        changed = true;
+                    }
+                    if changed {
+                        self.config.save();
+                    }
+                });
+                if ui.checkbox(&mut self.config.high_contrast, "高对比度模式").changed() {
+                    apply_theme(ui.ctx(), self.config.high_contrast);
+                    self.config.save();
+                }
+                ui.collapsing("健康提醒轮换", |ui| {
+                    ui.label("短休息依次轮换开启的提醒，时长为 0 时使用休息时长");
+                    let mut changed = false;
+                    for reminder in &mut self.config.reminders {
+                        ui.horizontal(|ui| {
+                            let kind = reminder.kind;
+                            changed |= ui.checkbox(&mut reminder.enabled, format!("{} {}", kind.icon(), kind.label())).changed();
+                            let minutes = egui::DragValue::new(&mut reminder.minutes).range(0..=MAX_SESSION_MINUTES).suffix(" 分");
+                            changed |= ui.add_enabled(reminder.enabled, minutes).changed();
+                        });
+                    }
+                    ui.horizontal(|ui| {
+                        ui.label("每日饮水目标:");
+                        let goal = egui::DragValue::new(&mut self.config.water_goal_cups).range(0..=30).suffix(" 杯");
+                        changed |= ui.add(goal).on_hover_text("0 表示不显示饮水统计").changed();
+                    });
+                    if changed {
+                        self.config.save();
+                    }
+                });
+                ui.collapsing("工作时间表", |ui| {
+                    let schedule = &mut self.config.schedule;
+                    let mut changed = ui.checkbox(&mut schedule.enabled, "仅在工作时间内提醒").changed();
+                    ui.add_enabled_ui(schedule.enabled, |ui| {
+                        for (day, name) in schedule.days.iter_mut().zip(WorkSchedule::DAY_NAMES) {
+                            ui.horizontal(|ui| {
+                                changed |= ui.checkbox(&mut day.enabled, name).changed();
+                                changed |= time_of_day_edit(ui, &mut day.start);
+                                ui.label("-");
+                                changed |= time_of_day_edit(ui, &mut day.end);
+                            });
+                        }
+                    });
+                    if changed {
+                        self.config.save();
+                    }
+                });
+                ui.horizontal(|ui| {
+                    let config = &mut self.config;
+                    let mut changed = ui.checkbox(&mut config.quiet_hours_enabled, "🌙 夜间静音").changed();
+                    ui.add_enabled_ui(config.quiet_hours_enabled, |ui| {
+                        changed |= time_of_day_edit(ui, &mut config.quiet_hours_start);
+                        ui.label("-");
+                        changed |= time_of_day_edit(ui, &mut config.quiet_hours_end);
+                    });
+                    if changed {
+                        config.save();
+                    }
+                });
+                ui.horizontal(|ui| {
+                    if ui.button("导出设置").clicked() {
+                        self.export_settings();
+                    }
+                    if ui.button("导入设置").clicked() {
+                        self.import_settings();
+                        apply_theme(ui.ctx(), self.config.high_contrast);
+                    }
+                });
+                // 修复了这里的调用错误
+                let mut auto_start = self.auto_start_enabled;
+                if ui.checkbox(&mut auto_start, "开机自启").changed() {
+                    self.request_auto_start(auto_start);
+                }
+            });
+            if settings.header_response.clicked() {
+                // 展开设置时重新数一次显示器，期间接上的新显示器也能选
+                self.monitor_count = list_monitors().len();
+            }
+            ui.collapsing("统计", |ui| self.render_stats(ui));
+            ui.collapsing("快捷键", |ui| {
+                egui::Grid::new("shortcuts").num_columns(2).show(ui, |ui| {
+                    for (key, action) in SHORTCUTS {
+                        ui.monospace(key.name());
+                        ui.label(action.label());
+                        ui.end_row();
+                    }
+                });
+                ui.label(egui::RichText::new("输入框有焦点时快捷键不生效").small().weak());
+            });
+            ui.collapsing("关于", |ui| {
+                ui.label(format!("休息提醒助手 v{}", APP_VERSION));
+                ui.separator();
+                ui.label(changelog_for(APP_VERSION));
+            });
+            ui.add_space(20.0);
+            let hide_label = if self.config.minimize_to_taskbar { "最小化" } else { "隐藏到托盘" };
+            if ui.button(hide_label).clicked() { self.should_hide = true; }
+        });
+    }
+
+    fn dismiss_whats_new(&mut self) {
+        self.show_whats_new = false;
+        self.config.last_seen_version = APP_VERSION.to_string();
+        self.config.save();
+    }
+
+    // 留空或点跳过都不保存
+    pub(crate) fn finish_note(&mut self, save: bool) {
+        let Some(mut note) = self.pending_note.take() else { return };
+        note.note = note.note.trim().to_string();
+        if !save || note.note.is_empty() {
+            return;
+        }
+        note.append();
+        self.recent_notes.push(note);
+        let excess = self.recent_notes.len().saturating_sub(SessionNote::RECENT);
+        self.recent_notes.drain(..excess);
+    }
+
+    fn render_note_prompt(&mut self, ctx: &egui::Context) {
+        let Some(note) = &mut self.pending_note else { return };
+        let mut result = None;
+        egui::Window::new("📝 这个番茄完成了什么？")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_BOTTOM, egui::vec2(0.0, -40.0))
+            .show(ctx, |ui| {
+                let response = ui.text_edit_singleline(&mut note.note);
+                if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                    result = Some(true);
+                }
+                ui.horizontal(|ui| {
+                    if ui.button("保存").clicked() {
+                        result = Some(true);
+                    }
+                    if ui.button("跳过").clicked() {
+                        result = Some(false);
+                    }
+                });
+            });
+        if let Some(save) = result {
+            self.finish_note(save);
+        }
+    }
+
+    fn render_whats_new(&mut self, ctx: &egui::Context) {
+        let mut dismissed = false;
+        egui::Window::new(format!("🎉 已更新到 v{}", APP_VERSION))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(changelog_for(APP_VERSION));
+                ui.add_space(8.0);
+                dismissed = ui.button("知道了").clicked();
+            });
+        if dismissed {
+            self.dismiss_whats_new();
+        }
+    }
+
+    // 一排番茄圆点：已完成的实心，剩余的空心
+    fn render_long_rest_progress(&self, ui: &mut egui::Ui) {
+        let Some((done, cycles)) = self.cycle_progress() else { return };
+        let remaining = cycles - done;
+        let text = if remaining == 0 {
+            format!("本轮 {} / {} · 下次休息为长休息 🌴", done, cycles)
+        } else {
+            format!("本轮 {} / {} · 距离长休息还有 {} 个番茄", done, cycles, remaining)
+        };
+        ui.label(text);
+
+        let radius = 6.0;
+        let spacing = 6.0;
+        let width = cycles as f32 * (radius * 2.0 + spacing) - spacing;
+        let (rect, _) = ui.allocate_exact_size(egui::vec2(width, radius * 2.0), egui::Sense::hover());
+        let tomato = egui::Color32::from_rgb(255, 99, 71);
+        for i in 0..cycles {
+            let center = egui::pos2(rect.left() + radius + i as f32 * (radius * 2.0 + spacing), rect.center().y);
+            if i < done {
+                ui.painter().circle_filled(center, radius, tomato);
+            } else {
+                ui.painter().circle_stroke(center, radius - 1.0, egui::Stroke::new(1.5, tomato));
+            }
+        }
+    }
+
+    // 从屏幕顶部一次撒下一批表情，复用休息时的下落动画
+    fn spawn_celebration(&mut self, ctx: &egui::Context, milestone: u64) {
+        self.spawn_burst(ctx, &["🎉", "🍅", "✨", "🏆"], 40);
+        self.celebration = Some((milestone, Instant::now()));
+    }
+
+    // 从窗口上方撒一把表情，复用休息时的下落动画
+    fn spawn_burst(&mut self, ctx: &egui::Context, emojis: &[&str], count: usize) {
+        let screen = ctx.input(|i| i.screen_rect);
+        let [min_speed, max_speed] = self.config.emoji_speed_range;
+        for _ in 0..count.min(MAX_DROPS.saturating_sub(self.drops.len())) {
+            self.drops.push(EmojiDrop {
+                emoji: emojis[fastrand::usize(..emojis.len())].to_string(),
+                x: fastrand::f32() * screen.width(),
+                y: -30.0 - fastrand::f32() * screen.height() * 0.5,
+                speed: min_speed + fastrand::f32() * (max_speed - min_speed),
+            });
+        }
+    }
+
+    // 修复了方法不存在的错误
+    // 画在面板自己的 painter 上时，先于控件绘制，表情就落在按钮下面
+    fn render_emojis(&self, painter: &egui::Painter) {
+        let font = egui::FontId::proportional(40.0);
+        for d in &self.drops {
+            painter.text(egui::pos2(d.x, d.y), egui::Align2::CENTER_CENTER, &d.emoji, font.clone(), egui::Color32::WHITE);
+        }
+    }
+}
+
+impl eframe::App for RestReminderApp {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+
+        // 保存窗口句柄 (只需要保存一次)
+        #[cfg(target_os = "windows")]
+        {
+            static INIT_HANDLE: std::sync::Once = std::sync::Once::new();
+            INIT_HANDLE.call_once(|| {
+                if let Ok(handle) = _frame.window_handle() {
+                    if let RawWindowHandle::Win32(h) = handle.as_raw() {
+                        let hwnd = h.hwnd.get() as *mut std::ffi::c_void;
+                        WINDOW_HANDLE.store(hwnd, Ordering::SeqCst);
+                        println!("保存窗口句柄: {:?}", hwnd);
+                    }
+                }
+            });
+        }
+
+        // --- 0. 检查是否需要退出 ---
+        if self.should_quit {
+            println!("正在退出应用程序...");
+            // 立即强制退出，避免任何延迟
+            std::process::exit(0);
+        }
+
+        // --- 1. 检查托盘请求 (使用原子变量而不是消息通道) ---
+        let mut handled_count = 0;
+
+        while let Ok(msg) = self.tray_receiver.try_recv() {
+            self.process_tray_message(msg);
+            handled_count += 1;
+        }
+
+        // 检查显示窗口和退出请求 (取出后重置标志)
+        let show = TRAY_SHOW_REQUEST.swap(false, Ordering::SeqCst);
+        let quit = TRAY_QUIT_REQUEST.swap(false, Ordering::SeqCst);
+        handled_count += self.apply_tray_flags(show, quit);
+
+        if handled_count > 0 {
+            println!("本轮处理了 {} 个托盘请求", handled_count);
+        }
+
+        // --- 2. 处理窗口关闭 -> 隐藏 ---
+        if ctx.input(|i| i.viewport().close_requested()) && !self.should_quit {
+            println!("用户点击关闭，转为隐藏模式");
+            ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+            self.should_hide = true;
+        }
+
+        // --- 3. 强制持续重绘和消息检查 ---
+        // 默认始终强制重绘，确保托盘消息被处理；开启低功耗时空闲隐藏状态下不重绘
+        let idle = self.can_stop_repainting(WINDOW_HIDDEN.load(Ordering::SeqCst));
+        if !idle {
+            ctx.request_repaint();
+            ctx.request_repaint_after(Duration::from_millis(50)); // 20fps for tray message checking
+        }
+
+        // --- 4. 状态刷新 ---
+        let now = chrono::Local::now().naive_local();
+        self.check_first_session_rollover(now.date());
+        let quiet_hours = self.config.is_quiet_hours(now);
+        if quiet_hours != self.quiet_hours {
+            println!("{}", if quiet_hours { "进入静音时段" } else { "静音时段结束" });
+            self.quiet_hours = quiet_hours;
+            if quiet_hours && (self.is_overlay_mode || self.is_rest_toast) {
+                // 休息中进入静音时段，收起已显示的休息提示
+                self.show_rest_display();
+            }
+        }
+        let off_hours = self.config.schedule.is_off_hours(now);
+        self.check_paused_nudge();
+        TIMER_RUNNING.store(self.start_time.is_some(), Ordering::SeqCst);
+        self.update_tray_icon();
+        self.update_tray_tooltip();
+        self.update_tray_status();
+        if off_hours != self.off_hours {
+            println!("{}", if off_hours { "进入非工作时间，暂停提醒" } else { "进入工作时间" });
+            self.off_hours = off_hours;
+        }
+        if off_hours && matches!(self.state, AppState::Working | AppState::Resting) {
+            self.pause();
+        }
+        self.apply_session_lock(SESSION_LOCKED.load(Ordering::SeqCst));
+        match self.state {
+            AppState::Resting => {
+                if !window_hidden(ctx) {
+                    self.update_emojis(ctx);
+                }
+                ctx.request_repaint_after(Duration::from_millis(16)); // ~60fps for animations
+            }
+            AppState::Working => {
+                ctx.request_repaint_after(Duration::from_millis(100)); // 更频繁的检查
+            }
+            AppState::Paused | AppState::Ready if !idle => {
+                ctx.request_repaint_after(Duration::from_millis(50)); // 暂停状态也要频繁检查托盘消息
+            }
+            AppState::Paused | AppState::Ready if self.config.paused_nudge_minutes > 0 => {
+                ctx.request_repaint_after(Duration::from_millis(250)); // 低功耗下也要按时催促、闪烁
+            }
+            AppState::Paused | AppState::Ready => {}
+        }
+        if self.state != AppState::Resting && !self.drops.is_empty() && !window_hidden(ctx) {
+            // 里程碑礼花在非休息状态也要继续下落
+            self.update_emojis(ctx);
+            ctx.request_repaint_after(Duration::from_millis(16));
+        }
+        self.check_clock_jump(Instant::now(), SystemTime::now());
+        self.sample_foreground_app();
+        self.check_overlay_fade_out();
+        self.tick();
+
+        // 任意输入都视为用户操作，重置空闲计时
+        if ctx.input(|i| !i.events.is_empty() || i.pointer.any_down()) {
+            self.last_interaction = Instant::now();
+        }
+        self.check_idle_hide();
+        self.check_auto_minimize();
+        self.check_overlay_preview();
+        self.check_stats_autosave();
+        self.check_skip_undo();
+        if let Some(message) = take_save_error() {
+            self.show_error(message);
+        }
+
+        // --- 4. 执行窗口命令 ---
+        self.route_hide_request();
+
+        if self.should_hide {
+            println!("正在隐藏窗口到托盘...");
+            ctx.send_viewport_cmd(egui::ViewportCommand::Visible(false));
+
+            // 同时使用 Windows API 强制隐藏
+            #[cfg(target_os = "windows")]
+            {
+                let hwnd = WINDOW_HANDLE.load(Ordering::SeqCst) as HWND;
+                if !hwnd.is_null() {
+                    unsafe {
+                        use winapi::um::winuser::ShowWindow;
+                        ShowWindow(hwnd, winapi::um::winuser::SW_HIDE);
+                        println!("使用 Windows API 隐藏窗口: {:?}", hwnd);
+                    }
+                }
+            }
+
+            WINDOW_HIDDEN.store(true, Ordering::SeqCst);
+            self.should_hide = false;
+            println!("窗口隐藏完成");
+        }
+
+        if self.should_show_from_tray && self.wayland {
+            // Wayland 不允许程序自行抢焦点/置顶，只恢复可见并请求合成器提醒用户
+            println!("Wayland 会话，跳过抢焦点，改为请求用户注意");
+            ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+            ctx.send_viewport_cmd(egui::ViewportCommand::Visible(true));
+            ctx.send_viewport_cmd(egui::ViewportCommand::RequestUserAttention(egui::UserAttentionType::Informational));
+            WINDOW_HIDDEN.store(false, Ordering::SeqCst);
+            self.should_show_from_tray = false;
+        }
+
+        if self.should_show_from_tray && !self.config.steal_focus_on_show {
+            // 只恢复可见性，不抢焦点 (很多合成器本来也会拒绝)
+            println!("正在唤醒窗口 (不抢占焦点)...");
+            ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+            ctx.send_viewport_cmd(egui::ViewportCommand::Visible(true));
+            WINDOW_HIDDEN.store(false, Ordering::SeqCst);
+            self.should_show_from_tray = false;
+        }
+
+        if self.should_show_from_tray {
+            println!("正在尝试唤醒窗口 (抢占焦点)...");
+
+            // 1. 基础 eframe 命令
+            ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+            ctx.send_viewport_cmd(egui::ViewportCommand::Visible(true));
+
+            // 2. 延迟一下再执行 Windows API 调用，确保窗口状态更新
+            std::thread::sleep(Duration::from_millis(100));
+
+            // 3. 使用 Windows API 强制操作
+            #[cfg(target_os = "windows")]
+            {
+                if let Ok(handle) = _frame.window_handle() {
+                    if let RawWindowHandle::Win32(h) = handle.as_raw() {
+                        let hwnd = h.hwnd.get() as HWND;
+                        println!("获取到窗口句柄: {:?}", hwnd);
+
+                        unsafe {
+                            // 先显示窗口
+                            ShowWindow(hwnd, SW_RESTORE);
+                            std::thread::sleep(Duration::from_millis(50));
+                            // 然后置顶
+                            let result = SetForegroundWindow(hwnd);
+                            println!("SetForegroundWindow 结果: {}", result);
+                        }
+                    } else {
+                        println!("不是 Win32 窗口句柄");
+                    }
+                } else {
+                    println!("无法获取窗口句柄");
+                }
+            }
+
+            // 4. 多次尝试获取焦点
+            for i in 0..3 {
+                ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+                ctx.request_repaint();
+                std::thread::sleep(Duration::from_millis(100));
+                println!("尝试获取焦点 {}/3", i + 1);
+            }
+
+            WINDOW_HIDDEN.store(false, Ordering::SeqCst);
+            self.should_show_from_tray = false;
+            println!("窗口显示逻辑执行完成");
+        }
+
+        if self.should_minimize {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(true));
+            self.should_minimize = false;
+        }
+
+        if !self.is_initialized {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Fullscreen(false));
+            self.is_initialized = true;
+        }
+        if self.should_fullscreen != self.was_fullscreen {
+            if self.should_fullscreen {
+                self.overlay_style = self.config.overlay_style();
+            }
+            let monitor_size = ctx.input(|i| i.viewport().monitor_size);
+            for command in overlay_window_commands(self.should_fullscreen, self.overlay_style, monitor_size) {
+                ctx.send_viewport_cmd(command);
+            }
+            if self.should_fullscreen {
+                // 先把窗口挪到目标显示器，全屏会落在窗口所在的显示器上；窗口模式直接居中
+                let monitors = list_monitors();
+                self.monitor_count = monitors.len();
+                let target = pick_monitor(&monitors, self.config.overlay_monitor).filter(|_| self.overlay_style != OverlayStyle::Windowed);
+                if let Some(monitor) = target {
+                    move_window_to_monitor(monitor);
+                }
+                self.secondary_monitors.clear();
+                self.secondary_placed.clear();
+                if self.config.overlay_all_monitors && self.overlay_style != OverlayStyle::Windowed {
+                    let point = match target {
+                        Some(m) => (m.left + m.width / 2, m.top + m.height / 2),
+                        None => {
+                            let ppp = ctx.pixels_per_point();
+                            let center = ctx.input(|i| i.viewport().outer_rect).map_or(egui::Pos2::ZERO, |rect| rect.center());
+                            ((center.x * ppp) as i32, (center.y * ppp) as i32)
+                        }
+                    };
+                    self.secondary_monitors = secondary_monitors(&monitors, point);
+                }
+                // 隐藏在托盘时也要把休息蒙层显示出来
+                ctx.send_viewport_cmd(egui::ViewportCommand::Visible(true));
+                WINDOW_HIDDEN.store(false, Ordering::SeqCst);
+                ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+                if self.config.intensity == Intensity::Strict {
+                    // 严格模式: 置顶并强制抢到前台
+                    ctx.send_viewport_cmd(egui::ViewportCommand::WindowLevel(egui::WindowLevel::AlwaysOnTop));
+                    force_foreground();
+                }
+            } else if !self.is_rest_toast {
+                self.secondary_monitors.clear();
+                self.secondary_placed.clear();
+                ctx.send_viewport_cmd(egui::ViewportCommand::WindowLevel(egui::WindowLevel::Normal));
+            }
+            self.was_fullscreen = self.should_fullscreen;
+        }
+        if self.is_rest_toast != self.was_rest_toast {
+            if self.is_rest_toast {
+                let size = egui::vec2(TOAST_SIZE[0], TOAST_SIZE[1]);
+                ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+                ctx.send_viewport_cmd(egui::ViewportCommand::Visible(true));
+                WINDOW_HIDDEN.store(false, Ordering::SeqCst);
+                ctx.send_viewport_cmd(egui::ViewportCommand::MinInnerSize(size));
+                ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(size));
+                if let Some(monitor) = ctx.input(|i| i.viewport().monitor_size) {
+                    let pos = egui::pos2(monitor.x - size.x - 24.0, monitor.y - size.y - 72.0);
+                    ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(pos));
+                }
+                ctx.send_viewport_cmd(egui::ViewportCommand::WindowLevel(egui::WindowLevel::AlwaysOnTop));
+            } else {
+                ctx.send_viewport_cmd(egui::ViewportCommand::WindowLevel(egui::WindowLevel::Normal));
+                ctx.send_viewport_cmd(egui::ViewportCommand::MinInnerSize(MIN_WINDOW_SIZE.into()));
+                ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(WINDOW_SIZE.into()));
+            }
+            self.was_rest_toast = self.is_rest_toast;
+        }
+
+        // 鼠标穿透：光标离开按钮附近时让点击落到下面的窗口
+        const CLICK_MARGIN: f32 = 40.0;
+        let click_through = self.is_overlay_mode
+            && self.config.overlay_click_through
+            && !self.overlay_button_area.is_some_and(|area| cursor_in_area(area.expand(CLICK_MARGIN), ctx.pixels_per_point()));
+        if click_through != self.click_through {
+            set_click_through(click_through);
+            self.click_through = click_through;
+        }
+
+        // --- 5. UI 渲染 ---
+        // 迷你倒计时是独立窗口，主窗口隐藏时也要显示
+        self.render_mini_overlay(ctx);
+        self.render_secondary_overlays(ctx);
+        // 隐藏或最小化时只需要计时和托盘处理，跳过界面构建
+        if window_hidden(ctx) {
+            return;
+        }
+        self.handle_shortcuts(ctx);
+        if self.is_overlay_mode {
+            self.render_overlay(ctx);
+        } else if self.is_rest_toast {
+            self.render_rest_toast(ctx);
+        } else {
+            self.render_main(ctx);
+            if self.show_whats_new {
+                self.render_whats_new(ctx);
+            }
+        }
+        if self.pending_note.is_some() && !self.is_rest_toast {
+            self.render_note_prompt(ctx);
+        }
+        if !self.drops.is_empty() && !self.is_rest_toast && self.config.emojis_above_ui {
+            self.render_emojis(&ctx.layer_painter(egui::LayerId::new(egui::Order::Foreground, egui::Id::new("emojis"))));
+        }
+    }
+}
+
+// 全屏会独占显示器，Alt+Tab 切走后蒙层可能被最小化；无边框模式改为去掉标题栏、
+// 最大化 (不盖住任务栏) 并置顶，看起来同样铺满屏幕，但仍能切换到其它窗口
+// 窗口模式只是把主窗口放大并居中到当前显示器，退出时恢复原来的大小
+pub(crate) fn overlay_window_commands(enter: bool, style: OverlayStyle, monitor_size: Option<egui::Vec2>) -> Vec<egui::ViewportCommand> {
+    use egui::ViewportCommand;
+    match (enter, style) {
+        (true, OverlayStyle::Fullscreen) => vec![ViewportCommand::Fullscreen(true)],
+        (false, OverlayStyle::Fullscreen) => vec![ViewportCommand::Fullscreen(false)],
+        (true, OverlayStyle::Borderless) => vec![
+            ViewportCommand::Decorations(false),
+            ViewportCommand::Maximized(true),
+            ViewportCommand::WindowLevel(egui::WindowLevel::AlwaysOnTop),
+        ],
+        (false, OverlayStyle::Borderless) => vec![ViewportCommand::Maximized(false), ViewportCommand::Decorations(true)],
+        (true, OverlayStyle::Windowed) => {
+            let size = egui::Vec2::from(REST_WINDOW_SIZE);
+            let mut commands = vec![ViewportCommand::Minimized(false), ViewportCommand::InnerSize(size)];
+            if let Some(monitor) = monitor_size {
+                commands.push(ViewportCommand::OuterPosition(((monitor - size) / 2.0).max(egui::Vec2::ZERO).to_pos2()));
+            }
+            commands
+        }
+        (false, OverlayStyle::Windowed) => vec![ViewportCommand::InnerSize(WINDOW_SIZE.into())],
+    }
+}
+
+pub(crate) fn changelog_for(version: &str) -> &'static str {
+    CHANGELOG.iter().find(|(v, _)| *v == version).map(|(_, notes)| *notes).unwrap_or("暂无更新说明")
+}
+
+// 设置中的单行输入：失去焦点或按回车时提交，返回是否提交了新值。
+// apply 返回 None 表示输入无效 (包括清空)，输入框恢复为 fallback；提交成功后在旁边短暂显示 ✔
+pub(crate) fn settings_field(
+    ui: &mut egui::Ui,
+    label: &str,
+    input: &mut String,
+    fallback: String,
+    apply: impl FnOnce(&str) -> Option<String>,
+) -> bool {
+    const CONFIRM_SECS: f64 = 1.5;
+    ui.horizontal(|ui| {
+        ui.label(label);
+        let response = ui.add(egui::TextEdit::singleline(input).id(settings_field_id(label)));
+        let submitted = response.lost_focus()
+            || (response.has_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)));
+        let now = ui.input(|i| i.time);
+        let mut committed = false;
+        if submitted {
+            match apply(input.trim()) {
+                Some(value) => {
+                    *input = value;
+                    committed = true;
+                    ui.ctx().data_mut(|d| d.insert_temp(response.id, now));
+                }
+                None => *input = fallback,
+            }
+        }
+        let confirmed_at = ui.ctx().data(|d| d.get_temp::<f64>(response.id));
+        if confirmed_at.is_some_and(|t| now - t < CONFIRM_SECS) {
+            ui.label(egui::RichText::new("✔").color(egui::Color32::from_rgb(80, 180, 80)));
+        }
+        committed
+    })
+    .inner
+}
+
+// 按标签固定输入框的 id，同步配置时据此认出用户正在编辑的输入框
+pub(crate) fn settings_field_id(label: &str) -> egui::Id {
+    egui::Id::new(("settings_field", label))
+}
+
+// 以 时:分 两个拖动框编辑当天的分钟数，返回是否修改
+pub(crate) fn time_of_day_edit(ui: &mut egui::Ui, minutes: &mut u32) -> bool {
+    let mut hour = *minutes / 60;
+    let mut minute = *minutes % 60;
+    let mut changed = ui.add(egui::DragValue::new(&mut hour).range(0..=23)).changed();
+    ui.label(":");
+    changed |= ui.add(egui::DragValue::new(&mut minute).range(0..=59)).changed();
+    *minutes = hour * 60 + minute;
+    changed
+}
+
+// 高对比度时覆盖亮/暗两套主题，关闭时恢复 egui 默认样式
+pub(crate) fn apply_theme(ctx: &egui::Context, high_contrast: bool) {
+    if high_contrast {
+        let visuals = high_contrast_visuals();
+        ctx.set_visuals_of(egui::Theme::Dark, visuals.clone());
+        ctx.set_visuals_of(egui::Theme::Light, visuals);
+    } else {
+        ctx.set_visuals_of(egui::Theme::Dark, egui::Visuals::dark());
+        ctx.set_visuals_of(egui::Theme::Light, egui::Visuals::light());
+    }
+}
+
+pub(crate) fn high_contrast_visuals() -> egui::Visuals {
+    use egui::{Color32, Stroke};
+    let mut visuals = egui::Visuals::dark();
+    visuals.panel_fill = Color32::BLACK;
+    visuals.window_fill = Color32::BLACK;
+    visuals.extreme_bg_color = Color32::BLACK;
+    visuals.faint_bg_color = Color32::BLACK;
+    visuals.window_stroke = Stroke::new(2.0, Color32::WHITE);
+    visuals.selection.bg_fill = Color32::YELLOW;
+    visuals.selection.stroke = Stroke::new(2.0, Color32::BLACK);
+    visuals.hyperlink_color = Color32::YELLOW;
+
+    let widgets = &mut visuals.widgets;
+    widgets.noninteractive.fg_stroke = Stroke::new(1.0, Color32::WHITE);
+    widgets.noninteractive.bg_stroke = Stroke::new(1.0, Color32::WHITE);
+    for w in [&mut widgets.inactive, &mut widgets.open] {
+        w.bg_fill = Color32::BLACK;
+        w.weak_bg_fill = Color32::BLACK;
+        w.bg_stroke = Stroke::new(2.0, Color32::YELLOW);
+        w.fg_stroke = Stroke::new(2.0, Color32::WHITE);
+    }
+    for w in [&mut widgets.hovered, &mut widgets.active] {
+        w.bg_fill = Color32::YELLOW;
+        w.weak_bg_fill = Color32::YELLOW;
+        w.bg_stroke = Stroke::new(2.0, Color32::WHITE);
+        w.fg_stroke = Stroke::new(2.0, Color32::BLACK);
+    }
+    visuals
+}
+
+// 中文字体候选，按顺序尝试；都不可用时使用 egui 内置字体 (中文会显示为方块)
+pub(crate) const FONT_CANDIDATES: [&str; 5] = [
+    "C:\\Windows\\Fonts\\msyh.ttc",
+    "C:\\Windows\\Fonts\\simhei.ttf",
+    "C:\\Windows\\Fonts\\simsun.ttc",
+    "/System/Library/Fonts/PingFang.ttc",
+    "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc",
+];
+
+// egui 遇到无法解析的字体会直接 panic，所以先用同样的解析库校验一遍
+pub(crate) fn load_first_font(candidates: &[&str]) -> Option<(String, Vec<u8>)> {
+    for path in candidates {
+        let Ok(data) = std::fs::read(path) else { continue };
+        match ab_glyph::FontRef::try_from_slice_and_index(&data, 0) {
+            Ok(_) => return Some((path.to_string(), data)),
+            Err(e) => println!("字体 {} 无法解析 ({})，尝试下一个", path, e),
+        }
+    }
+    None
+}
+
+pub(crate) fn setup_fonts(ctx: &egui::Context, warnings: &mut Vec<String>) {
+    let mut fonts = egui::FontDefinitions::default();
+    let Some((font_path, font_data)) = load_first_font(&FONT_CANDIDATES) else {
+        println!("没有可用的中文字体，使用内置字体");
+        warnings.push("没有找到可用的中文字体，界面文字可能显示为方块".to_string());
+        return;
+    };
+    println!("已加载字体: {}", font_path);
+    fonts.font_data.insert("system_ui".to_owned(), egui::FontData::from_owned(font_data));
+    fonts.families.get_mut(&egui::FontFamily::Proportional).unwrap().insert(0, "system_ui".to_owned());
+    fonts.families.get_mut(&egui::FontFamily::Monospace).unwrap().push("system_ui".to_owned());
+    ctx.set_fonts(fonts);
+}
+
+// 窗口行为只在创建窗口时设置，改了设置要重启才生效
+pub(crate) fn main_viewport(config: &AppConfig) -> egui::ViewportBuilder {
+    egui::ViewportBuilder::default()
+        .with_inner_size(WINDOW_SIZE)
+        .with_min_inner_size(MIN_WINDOW_SIZE)
+        .with_close_button(true)
+        .with_minimize_button(true)
+        .with_maximize_button(config.allow_maximize)
+        .with_resizable(config.allow_resize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 无头 UI 测试工具：直接用 egui::Context 跑帧，从输出的文本图形中查找控件位置并模拟点击
+    struct Harness {
+        ctx: egui::Context,
+        app: RestReminderApp,
+        events: Vec<egui::Event>,
+        shapes: Vec<egui::epaint::ClippedShape>,
+    }
+
+    impl Harness {
+        fn new() -> Self {
+            let (_tx, rx) = mpsc::channel();
+            let mut harness = Self {
+                ctx: egui::Context::default(),
+                app: RestReminderApp::from_parts(AppConfig::default(), rx, None),
+                events: vec![],
+                shapes: vec![],
+            };
+            harness.run();
+            harness
+        }
+
+        fn run(&mut self) {
+            let input = egui::RawInput {
+                screen_rect: Some(egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(400.0, 550.0))),
+                events: std::mem::take(&mut self.events),
+                ..Default::default()
+            };
+            let app = &mut self.app;
+            let output = self.ctx.run(input, |ctx| app.render_main(ctx));
+            self.shapes = output.shapes;
+        }
+
+        fn texts(&self) -> Vec<(String, egui::Rect)> {
+            fn collect(shape: &egui::Shape, out: &mut Vec<(String, egui::Rect)>) {
+                match shape {
+                    egui::Shape::Text(t) => out.push((t.galley.job.text.clone(), t.galley.rect.translate(t.pos.to_vec2()))),
+                    egui::Shape::Vec(v) => v.iter().for_each(|s| collect(s, out)),
+                    _ => {}
+                }
+            }
+            let mut out = vec![];
+            for clipped in &self.shapes {
+                collect(&clipped.shape, &mut out);
+            }
+            out
+        }
+
+        fn labels(&self) -> Vec<String> {
+            self.texts().into_iter().map(|(text, _)| text).collect()
+        }
+
+        fn click(&mut self, label: &str) {
+            let rect = self.texts().into_iter().find(|(text, _)| text == label)
+                .unwrap_or_else(|| panic!("找不到控件: {}", label)).1;
+            let pos = rect.center();
+            let button = |pressed| egui::Event::PointerButton {
+                pos,
+                button: egui::PointerButton::Primary,
+                pressed,
+                modifiers: egui::Modifiers::NONE,
+            };
+            self.events.push(egui::Event::PointerMoved(pos));
+            self.events.push(button(true));
+            self.run();
+            self.events.push(button(false));
+            self.run();
+            self.run();
+        }
+
+        fn key(&mut self, key: egui::Key) {
+            for pressed in [true, false] {
+                self.events.push(egui::Event::Key {
+                    key,
+                    physical_key: None,
+                    pressed,
+                    repeat: false,
+                    modifiers: egui::Modifiers::NONE,
+                });
+            }
+            self.run();
+        }
+    }
+
+    #[test]
+    fn starts_ready_with_start_button_only() {
+        let harness = Harness::new();
+        assert_eq!(harness.app.state, AppState::Ready);
+        let labels = harness.labels();
+        for label in ["开始专注", "准备开始", "25:00"] {
+            assert!(labels.iter().any(|l| l == label), "缺少标签 {:?}: {:?}", label, labels);
+        }
+        assert!(!labels.iter().any(|l| l == "暂停" || l == "继续"), "{:?}", labels);
+    }
+
+    #[test]
+    fn start_and_pause_buttons_switch_state() {
+        let mut harness = Harness::new();
+
+        harness.click("开始专注");
+        assert_eq!(harness.app.state, AppState::Working);
+        assert!(harness.app.start_time.is_some());
+        assert!(harness.labels().iter().any(|l| l == "🔥 专注中"));
+
+        harness.click("暂停");
+        assert_eq!(harness.app.state, AppState::Paused);
+        assert!(harness.app.start_time.is_none());
+        assert!(harness.labels().iter().any(|l| l == "⏸ 已暂停"));
+        harness.click("继续");
+        assert_eq!(harness.app.state, AppState::Working);
+        assert!(harness.app.start_time.is_some());
+    }
+
+    #[test]
+    fn no_emoji_spawn_with_zero_screen_rect() {
+        let (_tx, rx) = mpsc::channel();
+        let mut app = RestReminderApp::from_parts(AppConfig::default(), rx, None);
+        app.start_rest();
+        let ctx = egui::Context::default();
+        for _ in 0..200 {
+            let input = egui::RawInput {
+                screen_rect: Some(egui::Rect::from_min_size(egui::Pos2::ZERO, egui::Vec2::ZERO)),
+                ..Default::default()
+            };
+            let _ = ctx.run(input, |ctx| app.update_emojis(ctx));
+        }
+        assert!(app.drops.is_empty());
+    }
+
+    #[test]
+    fn whats_new_shown_once_after_update() {
+        let (_tx, rx) = mpsc::channel();
+        let fresh = RestReminderApp::from_parts(AppConfig::default(), rx, None);
+        assert!(!fresh.show_whats_new, "全新安装不弹窗");
+
+        let (_tx, rx) = mpsc::channel();
+        let config = AppConfig { last_seen_version: "0.0.1".to_string(), ..Default::default() };
+        let mut app = RestReminderApp::from_parts(config, rx, None);
+        assert!(app.show_whats_new);
+        app.dismiss_whats_new();
+        assert!(!app.show_whats_new);
+        assert_eq!(app.config.last_seen_version, APP_VERSION);
+
+        assert_ne!(changelog_for(APP_VERSION), "暂无更新说明", "当前版本需要有更新说明");
+    }
+
+    #[test]
+    fn settings_field_commits_on_enter_and_reverts_empty() {
+        let mut harness = Harness::new();
+        harness.ctx.style_mut(|s| s.animation_time = 0.0);
+        harness.click("设置");
+
+        harness.click("25");
+        harness.key(egui::Key::Backspace);
+        harness.key(egui::Key::Backspace);
+        harness.events.push(egui::Event::Text("40".to_string()));
+        harness.run();
+        harness.key(egui::Key::Enter);
+        assert_eq!(harness.app.config.work_minutes, 40);
+        assert!(harness.labels().iter().any(|l| l == "✔"), "提交后显示确认标记");
+
+        harness.click("40");
+        harness.key(egui::Key::Backspace);
+        harness.key(egui::Key::Backspace);
+        harness.key(egui::Key::Enter);
+        assert_eq!(harness.app.config.work_minutes, 40);
+        assert_eq!(harness.app.work_input, "40", "清空后恢复为上次的有效值");
+
+        harness.run();
+        harness.click("40");
+        harness.key(egui::Key::Backspace);
+        harness.key(egui::Key::Backspace);
+        harness.events.push(egui::Event::Text("0:20".to_string()));
+        harness.run();
+        harness.key(egui::Key::Enter);
+        assert_eq!(harness.app.config.work_minutes, 40, "不是整分钟不会悄悄舍入");
+        assert_eq!(harness.app.work_input, "40");
+        assert_eq!(harness.app.error_toast.as_ref().map(|(message, _)| message.as_str()), Some("这一项只能填整分钟，例如 25"));
+    }
+
+    #[test]
+    fn breathing_phase_cycle() {
+        assert_eq!(breathing_phase(0.0), (0.0, "吸气"));
+        assert_eq!(breathing_phase(BREATHING_IN_SECS).1, "屏息");
+        assert_eq!(breathing_phase(BREATHING_IN_SECS + 1.0).0, 1.0);
+        let (scale, hint) = breathing_phase(BREATHING_CYCLE_SECS - 0.001);
+        assert_eq!(hint, "呼气");
+        assert!(scale < 0.01, "呼气结束时缩回最小: {}", scale);
+    }
+
+    #[test]
+    fn emoji_settings_bounded_and_capped() {
+        let mut config = AppConfig::default();
+        config.set_emoji_spawn_rate(5.0);
+        assert_eq!(config.emoji_spawn_rate, 1.0);
+        config.set_emoji_spawn_rate(f32::NAN);
+        assert_eq!(config.emoji_spawn_rate, 0.0);
+        config.set_emoji_speed_range([5000.0, 0.0]);
+        assert_eq!(config.emoji_speed_range, [10.0, 1000.0], "越界截断，大小颠倒时交换");
+
+        config.set_emoji_spawn_rate(1.0);
+        let (_tx, rx) = mpsc::channel();
+        let mut app = RestReminderApp::from_parts(config, rx, None);
+        app.start_rest();
+        let ctx = egui::Context::default();
+        for _ in 0..(MAX_DROPS * 2) {
+            let input = egui::RawInput {
+                screen_rect: Some(egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(800.0, 100_000.0))),
+                ..Default::default()
+            };
+            let _ = ctx.run(input, |ctx| app.update_emojis(ctx));
+        }
+        assert_eq!(app.drops.len(), MAX_DROPS);
+    }
+
+    #[test]
+    fn broken_font_falls_through_to_next_candidate() {
+        let broken = std::env::temp_dir().join(format!("remindrest-broken-{}.ttc", std::process::id()));
+        std::fs::write(&broken, b"not a font").unwrap();
+        let broken = broken.to_string_lossy().to_string();
+        assert_eq!(load_first_font(&["/nonexistent/font.ttc", &broken]), None);
+        std::fs::remove_file(&broken).unwrap();
+    }
+
+    #[test]
+    fn repainting_stops_only_when_idle_and_hidden() {
+        let (_tx, rx) = mpsc::channel();
+        let config = AppConfig { idle_repaint_pause: true, ..Default::default() };
+        let mut app = RestReminderApp::from_parts(config, rx, None);
+        assert!(app.can_stop_repainting(true));
+        assert!(!app.can_stop_repainting(false), "窗口可见时照常刷新");
+        app.should_show_from_tray = true;
+        assert!(!app.can_stop_repainting(true), "有待处理的显示请求");
+        app.should_show_from_tray = false;
+        app.start_work();
+        assert!(!app.can_stop_repainting(true), "计时中需要继续刷新");
+        app.pause();
+        assert!(app.can_stop_repainting(true));
+        app.config.idle_repaint_pause = false;
+        assert!(!app.can_stop_repainting(true));
+    }
+
+    #[test]
+    fn imported_settings_are_migrated_and_applied() {
+        let old = r#"{"work_minutes": 50, "rest_minutes": 10, "skip_nag_threshold": 5}"#;
+        let config = AppConfig::parse_as(old, ConfigFormat::Json).unwrap();
+        assert_eq!(config.config_version, CONFIG_VERSION, "没有版本号的旧文件迁移到当前版本");
+        assert_eq!(config.work_minutes, 50);
+        let newer = format!(r#"{{"config_version": {}, "work_minutes": 50}}"#, CONFIG_VERSION + 1);
+        assert!(AppConfig::parse_as(&newer, ConfigFormat::Json).err().unwrap().contains("更新的版本"));
+        assert!(AppConfig::parse_as("[1, 2]", ConfigFormat::Json).is_err());
+        assert!(AppConfig::parse_as(r#"{"work_minutes": "很久"}"#, ConfigFormat::Json).is_err());
+
+        let (_tx, rx) = mpsc::channel();
+        let mut app = RestReminderApp::from_parts(AppConfig::default(), rx, None);
+        app.current_task = "写周报".to_string();
+        app.config.current_task = "写周报".to_string();
+        app.config.last_seen_version = APP_VERSION.to_string();
+        app.apply_imported_config(config);
+        assert_eq!(app.work_input, "50");
+        assert_eq!(app.skip_nag_input, "5");
+        assert_eq!(app.time_remaining, Duration::from_secs(50 * 60));
+        assert_eq!(app.config.current_task, "写周报", "本机的任务不被覆盖");
+        assert_eq!(app.config.last_seen_version, APP_VERSION);
+    }
+
+    #[test]
+    fn startup_warnings_banner_can_be_dismissed() {
+        let mut harness = Harness::new();
+        harness.app.startup_warnings = vec!["没有找到可用的中文字体，界面文字可能显示为方块".to_string()];
+        harness.run();
+        assert!(harness.labels().iter().any(|text| text.contains("中文字体")));
+        harness.click("知道了");
+        assert!(harness.app.startup_warnings.is_empty());
+        assert!(!harness.labels().iter().any(|text| text.contains("中文字体")));
+    }
+
+    #[test]
+    fn work_done_celebration_expires() {
+        let mut harness = Harness::new();
+        harness.app.config.celebrate_work_done = true;
+        harness.app.set_state_for_test(AppState::Working, Duration::from_secs(1));
+        harness.app.advance_clock(Duration::from_secs(2));
+        harness.app.tick();
+        assert_eq!(harness.app.state, AppState::Resting);
+        harness.app.is_overlay_mode = false;
+        harness.run();
+        assert!(harness.labels().iter().any(|text| text == "做得好! 🎉"));
+        assert!(!harness.app.drops.is_empty(), "放了一把表情");
+        harness.app.advance_clock(WORK_DONE_TOAST + Duration::from_secs(1));
+        harness.run();
+        assert!(harness.app.work_done.is_none());
+        assert!(!harness.labels().iter().any(|text| text == "做得好! 🎉"));
+    }
+
+    #[test]
+    fn borderless_overlay_never_goes_fullscreen() {
+        use egui::ViewportCommand;
+        assert_eq!(overlay_window_commands(true, OverlayStyle::Fullscreen, None), vec![ViewportCommand::Fullscreen(true)]);
+        for enter in [true, false] {
+            let commands = overlay_window_commands(enter, OverlayStyle::Borderless, None);
+            assert!(!commands.iter().any(|c| matches!(c, ViewportCommand::Fullscreen(_))));
+            assert!(commands.contains(&ViewportCommand::Decorations(!enter)));
+        }
+        let commands = overlay_window_commands(true, OverlayStyle::Borderless, None);
+        assert!(commands.contains(&ViewportCommand::WindowLevel(egui::WindowLevel::AlwaysOnTop)));
+    }
+
+    #[test]
+    fn duration_chips_set_minutes_and_inputs() {
+        let mut harness = Harness::new();
+        harness.click("设置");
+        harness.click("45分");
+        assert_eq!(harness.app.config.work_minutes, 45);
+        assert_eq!(harness.app.work_input, "45");
+        assert_eq!(harness.app.time_remaining, Duration::from_secs(45 * 60), "未开始时倒计时跟着变");
+        harness.click("10分");
+        assert_eq!(harness.app.config.rest_minutes, 10);
+        assert_eq!(harness.app.rest_input, "10");
+    }
+
+    #[test]
+    fn mini_overlay_saves_position_when_closed() {
+        let (_tx, rx) = mpsc::channel();
+        let config = AppConfig { mini_overlay: true, ..Default::default() };
+        let mut app = RestReminderApp::from_parts(config, rx, None);
+        let ctx = egui::Context::default();
+        app.set_state_for_test(AppState::Working, Duration::from_secs(600));
+        let _ = ctx.run(Default::default(), |ctx| app.render_mini_overlay(ctx));
+        assert_eq!(app.mini_overlay_pos, None, "内嵌显示时没有独立窗口位置");
+
+        app.mini_overlay_pos = Some(egui::pos2(40.0, 60.0));
+        app.set_state_for_test(AppState::Paused, Duration::from_secs(600));
+        let _ = ctx.run(Default::default(), |ctx| app.render_mini_overlay(ctx));
+        assert_eq!(app.config.mini_overlay_pos, Some([40.0, 60.0]));
+        assert_eq!(app.mini_overlay_pos, None);
+    }
+
+    #[test]
+    fn sync_inputs_skips_field_being_edited() {
+        let (_tx, rx) = mpsc::channel();
+        let mut app = RestReminderApp::from_parts(AppConfig::default(), rx, None);
+        app.work_input = "4".to_string();
+        app.rest_input = "1".to_string();
+        app.focused_widget = Some(settings_field_id("专注时长(分):"));
+        app.config.work_minutes = 50;
+        app.config.rest_minutes = 10;
+        app.sync_inputs_from_config();
+        assert_eq!(app.work_input, "4", "正在编辑的输入框不被覆盖");
+        assert_eq!(app.rest_input, "10");
+        app.focused_widget = None;
+        app.sync_inputs_from_config();
+        assert_eq!(app.work_input, "50");
+    }
+
+    #[test]
+    fn audio_device_status_reports_missing_devices() {
+        let none: Result<Vec<String>, String> = Ok(vec![]);
+        assert!(audio_device_status(&none, &None).contains("未找到音频输出设备"));
+        assert!(audio_device_status(&Err("host error".to_string()), &None).contains("host error"));
+        let devices = Ok(vec!["扬声器".to_string(), "耳机".to_string()]);
+        assert_eq!(audio_device_status(&devices, &None), "✔ 找到 2 个输出设备，当前使用系统默认");
+        assert_eq!(audio_device_status(&devices, &Some("耳机".to_string())), "✔ 找到 2 个输出设备，当前使用 耳机");
+        assert!(audio_device_status(&devices, &Some("HDMI".to_string())).contains("不可用"));
+    }
+
+    #[test]
+    fn windowed_rest_is_centered_and_not_fullscreen() {
+        use egui::ViewportCommand;
+        let commands = overlay_window_commands(true, OverlayStyle::Windowed, Some(egui::vec2(1920.0, 1080.0)));
+        assert!(!commands.iter().any(|c| matches!(c, ViewportCommand::Fullscreen(_))));
+        assert!(commands.contains(&ViewportCommand::InnerSize(REST_WINDOW_SIZE.into())));
+        assert!(commands.contains(&ViewportCommand::OuterPosition(egui::pos2(640.0, 280.0))));
+        assert_eq!(overlay_window_commands(false, OverlayStyle::Windowed, None), vec![ViewportCommand::InnerSize(WINDOW_SIZE.into())]);
+
+        let mut config = AppConfig { rest_window_mode: RestWindowMode::Windowed, overlay_borderless: true, ..Default::default() };
+        assert_eq!(config.overlay_style(), OverlayStyle::Windowed);
+        config.intensity = Intensity::Strict;
+        assert_eq!(config.overlay_style(), OverlayStyle::Borderless, "严格模式不用窗口模式");
+        config.overlay_borderless = false;
+        assert_eq!(config.overlay_style(), OverlayStyle::Fullscreen);
+    }
+
+    #[test]
+    fn shortcuts_follow_button_rules() {
+        let (_tx, rx) = mpsc::channel();
+        let mut app = RestReminderApp::from_parts(AppConfig::default(), rx, None);
+        app.apply_shortcut(ShortcutAction::TogglePause);
+        assert_eq!(app.state, AppState::Working);
+        app.apply_shortcut(ShortcutAction::TogglePause);
+        assert_eq!(app.state, AppState::Paused);
+        app.apply_shortcut(ShortcutAction::SkipRest);
+        assert_eq!(app.state, AppState::Paused, "不在休息时 Esc 无效");
+        app.apply_shortcut(ShortcutAction::Reset);
+        assert_eq!(app.state, AppState::Ready);
+
+        app.set_state_for_test(AppState::Resting, Duration::from_secs(60));
+        app.apply_shortcut(ShortcutAction::Reset);
+        assert_eq!(app.state, AppState::Resting, "休息中不能用 R 重置");
+        app.apply_shortcut(ShortcutAction::SkipRest);
+        assert_eq!(app.state, AppState::Ready);
+
+        let mut harness = Harness::new();
+        harness.click("快捷键");
+        let labels = harness.labels();
+        for (key, action) in SHORTCUTS {
+            assert!(labels.iter().any(|l| l == key.name()));
+            assert!(labels.iter().any(|l| l == action.label()));
+        }
+    }
+
+    #[test]
+    fn emojis_are_drawn_below_buttons_by_default() {
+        let mut harness = Harness::new();
+        let pos = harness.texts().into_iter().find(|(text, _)| text == "开始专注").unwrap().1.center();
+        harness.app.drops.push(EmojiDrop { emoji: "🍵".to_string(), x: pos.x, y: pos.y, speed: 0.0 });
+        harness.run();
+        let labels = harness.labels();
+        let emoji = labels.iter().position(|l| l == "🍵").expect("表情画在主界面上");
+        let button = labels.iter().position(|l| l == "开始专注").unwrap();
+        assert!(emoji < button, "表情先画，按钮文字盖在上面");
+        harness.click("开始专注");
+        assert_eq!(harness.app.state, AppState::Working, "表情不挡住点击");
+    }
+
+    #[test]
+    fn start_is_disabled_during_skip_cooldown() {
+        let mut harness = Harness::new();
+        harness.app.config.skip_cooldown_secs = 30;
+        harness.app.set_state_for_test(AppState::Resting, Duration::from_secs(60));
+        harness.app.skip_rest();
+        harness.run();
+        assert!(harness.labels().iter().any(|l| l.contains("秒后才能开始专注")));
+        harness.click("开始专注");
+        assert_eq!(harness.app.state, AppState::Ready, "冷却中不能开始");
+
+        harness.app.advance_clock(Duration::from_secs(30));
+        harness.run();
+        harness.click("开始专注");
+        assert_eq!(harness.app.state, AppState::Working);
+    }
+
+    #[test]
+    fn window_behavior_comes_from_config() {
+        let viewport = main_viewport(&AppConfig::default());
+        assert_eq!(viewport.maximize_button, Some(false));
+        assert_eq!(viewport.resizable, Some(true));
+        let config = AppConfig { allow_maximize: true, allow_resize: false, ..Default::default() };
+        let viewport = main_viewport(&config);
+        assert_eq!(viewport.maximize_button, Some(true));
+        assert_eq!(viewport.resizable, Some(false));
+    }
+
+    #[test]
+    fn main_content_stays_centered_when_wide() {
+        let mut harness = Harness::new();
+        let input = egui::RawInput {
+            screen_rect: Some(egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(1600.0, 900.0))),
+            ..Default::default()
+        };
+        let app = &mut harness.app;
+        harness.shapes = harness.ctx.run(input, |ctx| app.render_main(ctx)).shapes;
+        let button = harness.texts().into_iter().find(|(text, _)| text == "开始专注").unwrap().1;
+        assert!((button.center().x - 800.0).abs() < 1.0, "按钮在窗口中间");
+        let start = harness.texts().into_iter().map(|(_, rect)| rect.left()).fold(f32::MAX, f32::min);
+        assert!(start >= (1600.0 - MAIN_CONTENT_WIDTH) / 2.0, "内容不超出一栏宽度");
+    }
+
+    #[test]
+    fn daily_plan_is_per_day_with_last_plan_as_default() {
+        let mut stats = Stats::default();
+        let day1 = chrono::NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+        let day2 = day1.succ_opt().unwrap();
+        assert_eq!(stats.planned_on(day1), 0);
+        stats.set_plan(day1, 8);
+        assert_eq!(stats.planned_on(day2), 8, "新的一天沿用上次的计划");
+        stats.set_plan(day2, 5);
+        assert_eq!(stats.planned_on(day1), 8, "每天的计划分开保存");
+        assert_eq!(stats.planned_on(day2), 5);
+
+        let mut harness = Harness::new();
+        harness.app.stats.set_plan(today(), 8);
+        harness.app.stats.record_work(today(), 25);
+        harness.run();
+        assert!(harness.labels().iter().any(|l| l == "今日计划 1 / 8 🍅"));
+    }
+
+    #[test]
+    fn skipped_rest_can_be_undone_within_grace_window() {
+        let mut harness = Harness::new();
+        harness.app.config.smooth_transitions = false;
+        harness.app.task_queue = vec!["下一个".to_string()];
+        harness.app.set_state_for_test(AppState::Resting, Duration::from_secs(120));
+        harness.app.advance_clock(Duration::from_secs(20));
+        harness.app.skip_rest();
+        assert_eq!(harness.app.state, AppState::Ready);
+        assert!(!harness.app.should_minimize, "撤销时间内不收起窗口");
+        harness.run();
+        harness.click("撤销");
+        let app = &harness.app;
+        assert_eq!(app.state, AppState::Resting);
+        assert!(app.time_remaining.abs_diff(Duration::from_secs(100)) < Duration::from_secs(1), "按跳过时剩余的时间继续");
+        assert_eq!(app.consecutive_skips, 0);
+        assert!(app.stats.day(today()).is_none_or(|day| day.skipped_rests == 0), "统计回到跳过前");
+        assert_eq!(app.task_queue, vec!["下一个".to_string()]);
+
+        harness.app.skip_rest();
+        harness.app.advance_clock(Duration::from_secs(harness.app.config.skip_undo_secs));
+        harness.app.check_skip_undo();
+        assert!(harness.app.skip_undo.is_none());
+        assert!(harness.app.should_minimize, "过了撤销时间再收起");
+    }
+
+    #[test]
+    fn paused_duration_is_shown_only_while_paused() {
+        assert_eq!(format_elapsed_minutes(Duration::from_secs(30)), "不到 1 分钟");
+        assert_eq!(format_elapsed_minutes(Duration::from_secs(12 * 60 + 5)), "12 分钟");
+        assert_eq!(format_elapsed_minutes(Duration::from_secs(75 * 60)), "1 小时 15 分钟");
+
+        let mut harness = Harness::new();
+        assert!(harness.app.paused_for().is_none(), "从未开始时不显示");
+        harness.app.start_work();
+        harness.app.pause();
+        harness.app.advance_clock(Duration::from_secs(12 * 60));
+        harness.run();
+        assert!(harness.labels().iter().any(|l| l == "已暂停 12 分钟"));
+        harness.app.resume();
+        assert!(harness.app.paused_for().is_none());
+    }
+
+    #[test]
+    fn enabling_auto_start_asks_first() {
+        let mut harness = Harness::new();
+        harness.app.request_auto_start(true);
+        assert_eq!(harness.app.confirm_auto_start, Some(false));
+        // 窗口第一帧只量尺寸不显示
+        harness.run();
+        harness.run();
+        assert!(harness.labels().iter().any(|l| l.contains(AUTO_START_RUN_KEY)), "说明写入的注册表项");
+        harness.click("不再提示");
+        harness.click("确定开启");
+        assert!(harness.app.confirm_auto_start.is_none());
+        assert!(harness.app.config.auto_start_confirmed);
+
+        harness.app.request_auto_start(true);
+        assert!(harness.app.confirm_auto_start.is_none(), "选了不再提示后直接开启");
+        harness.app.config.auto_start_confirmed = false;
+        harness.app.request_auto_start(false);
+        assert!(harness.app.confirm_auto_start.is_none(), "关闭不需要确认");
+    }
+}